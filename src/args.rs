@@ -1,12 +1,68 @@
-use clap::{Parser, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 /// CLI struct to parse arguments
 #[derive(Debug, Clone, Parser)]
 #[clap(author, version, about)]
+#[allow(clippy::upper_case_acronyms)]
 pub struct CLI {
-    /// List of words to calculate the values of
+    /// Numerology tools beyond word values
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// List of words to calculate the values of ("-" reads words from stdin at that point)
     pub words: Vec<String>,
 
+    /// Read words from stdin after the other words, even if it is a terminal
+    #[arg(long = "stdin", conflicts_with = "no_stdin")]
+    pub stdin: bool,
+
+    /// Never read words from stdin, even if it is not a terminal; also skips the interactive prompt
+    #[arg(long = "no-stdin")]
+    pub no_stdin: bool,
+
+    /// Read words from files (can be repeated, and accepts glob patterns like "names/*.txt")
+    #[arg(short = 'i', long = "input")]
+    pub input: Vec<String>,
+
+    /// Read the input as CSV and append the values of one column to each row
+    #[arg(long = "csv", requires = "column")]
+    pub csv: bool,
+
+    /// CSV column to evaluate, by header name or by number (starting at 1)
+    #[arg(long = "column")]
+    pub column: Option<String>,
+
+    /// Field delimiter for CSV input
+    #[arg(long = "csv-delimiter", default_value_t = ',')]
+    pub csv_delimiter: char,
+
+    /// CSV input has no header row
+    #[arg(long = "no-header")]
+    pub no_header: bool,
+
+    /// Split words on any of these characters instead of whitespace (accepts \t, \n, \r and \0)
+    #[arg(long = "delimiter", conflicts_with = "split_regex")]
+    pub delimiter: Option<String>,
+
+    /// Split words on matches of this regular expression instead of whitespace
+    #[arg(long = "split-regex")]
+    pub split_regex: Option<String>,
+
+    /// Input lines are separated by NUL instead of newline, and each is a single word
+    /// unless --delimiter or --split-regex is given
+    #[arg(short = 'z', long = "null-data")]
+    pub null_data: bool,
+
+    /// What to do with hyphens in words (e.g. Mary-Jane)
+    #[arg(long = "hyphens")]
+    #[clap(value_enum, default_value_t=PunctuationRule::Keep)]
+    pub hyphens: PunctuationRule,
+
+    /// What to do with apostrophes in words (e.g. O'Brien)
+    #[arg(long = "apostrophes")]
+    #[clap(value_enum, default_value_t=PunctuationRule::Keep)]
+    pub apostrophes: PunctuationRule,
+
     /// Fast Mode
     #[arg(short = 'f', long = "fast")]
     pub fast: bool,
@@ -16,11 +72,11 @@ pub struct CLI {
     pub recursive: bool,
 
     /// Limit output verbosity (e.g., omit individual letter values)
-    #[arg(short = 'l', long = "less")]
+    #[arg(short = 'l', long = "less", global = true)]
     pub less: bool,
 
     /// Eliminate formatting and extra text (simple output)
-    #[arg(short = 'R', long = "raw")]
+    #[arg(short = 'R', long = "raw", global = true)]
     pub raw: bool,
 
     /// Exclude total overall value from the output
@@ -28,26 +84,245 @@ pub struct CLI {
     pub no_total: bool,
 
     /// Format the output as json
-    #[arg(long = "json")]
+    #[arg(long = "json", global = true)]
     pub json: bool,
 
+    /// Output format for word values, the table and the forecast, chart and dates
+    /// commands (--json is the same as --format json)
+    #[arg(long = "format", conflicts_with = "json", global = true)]
+    #[clap(value_enum, default_value_t=OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Print each word with this template, e.g. '{word:<12}\t{value:>3}\t{reduced}'
+    /// (fields: word, value, reduced, letters, transliterated, file, index)
+    #[arg(long = "template", conflicts_with = "template_file")]
+    pub template: Option<String>,
+
+    /// Print the total with this template, e.g. 'Total: {total} ({reduced})'
+    /// (fields: total, reduced, words)
+    #[arg(long = "total-template", conflicts_with = "template_file")]
+    pub total_template: Option<String>,
+
+    /// Render the whole report with a Jinja template file, given the --json data model
+    #[arg(long = "template-file")]
+    pub template_file: Option<String>,
+
+    /// Also write the word values to an Excel workbook (.xlsx), rewritten after each prompt with -r
+    #[arg(short = 'o', long = "output", value_parser = parse_xlsx_path)]
+    pub output: Option<String>,
+
+    /// Also record the run and its word values in a SQLite database (created if missing); one run per prompt with -r
+    #[arg(long = "sqlite")]
+    pub sqlite: Option<String>,
+
+    /// Print the JSON Schema of the --json output
+    #[arg(long = "json-schema")]
+    pub json_schema: bool,
+
+    /// How word values are laid out in the text output
+    #[arg(long = "layout")]
+    #[clap(value_enum, default_value_t=WordLayout::List)]
+    pub layout: WordLayout,
+
     /// Print out the table used to determine the values
     #[arg(long = "table")]
     pub table: bool,
 
     /// Whether to print output with color
-    #[arg(long)]
+    #[arg(long, global = true)]
     #[clap(value_enum, default_value_t=Color::Auto)]
     pub color: Color,
 
     /// Whether to print output with decorations (bold, italic, etc.)
-    #[arg(long)]
+    #[arg(long, global = true)]
     #[clap(value_enum, default_value_t=Decorations::Auto)]
     pub decorations: Decorations,
 
     /// Silence Extra Output (such as "Note:")
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub quiet: bool,
+
+    /// Fail if any character has no value in the table
+    ///
+    /// The words given as arguments are checked before anything is written; for
+    /// words read from stdin or files, the output up to the failing word is kept
+    /// (JSON and HTML documents are closed).
+    #[arg(long = "strict", conflicts_with = "warn_unknown")]
+    pub strict: bool,
+
+    /// Warn about characters that have no value in the table
+    #[arg(long = "warn-unknown")]
+    pub warn_unknown: bool,
+
+    /// Transliterate names from another script to Latin before evaluating them
+    #[arg(long = "transliterate", global = true)]
+    #[clap(value_enum)]
+    pub transliterate: Option<Scheme>,
+
+    /// How digits in words are scored
+    #[arg(long = "digits", global = true)]
+    #[clap(value_enum, default_value_t=DigitMode::Value)]
+    pub digits: DigitMode,
+
+    /// Use the letters of a language (digraphs, ligatures and special letters)
+    #[arg(long = "locale", global = true)]
+    #[clap(value_enum)]
+    pub locale: Option<LocaleName>,
+
+    /// JSON file of extra locale letters, each mapped to a spelling or a value
+    /// (e.g. {"ch": 8, "ß": "ss"})
+    #[arg(long = "locale-file", global = true)]
+    pub locale_file: Option<String>,
+
+    /// Score accented letters as distinct characters instead of like their base letters
+    #[arg(long = "no-normalize", global = true)]
+    pub no_normalize: bool,
+
+    /// Exact format for dates (e.g. "%d.%m.%Y"), overriding automatic detection
+    #[arg(long = "date-format", global = true)]
+    pub date_format: Option<String>,
+
+    /// Order of day, month and year in numeric dates: settles ambiguous dates like
+    /// 03/04/1990 and allows two-digit years like 90/03/14 (ymd) or 14/03/90 (dmy)
+    #[arg(long = "date-order", global = true)]
+    #[clap(value_enum)]
+    pub date_order: Option<DateOrder>,
+}
+
+impl CLI {
+    /// The output format, taking --json into account
+    pub fn output_format(&self) -> OutputFormat {
+        if self.json {
+            OutputFormat::Json
+        } else {
+            self.format
+        }
+    }
+}
+
+/// Accept only paths of Excel workbooks for `--output`
+fn parse_xlsx_path(path: &str) -> Result<String, String> {
+    if path.to_lowercase().ends_with(".xlsx") {
+        Ok(path.to_string())
+    } else {
+        Err("only .xlsx workbooks are supported".to_string())
+    }
+}
+
+/// Subcommands
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Year-by-year transit letters and Essence numbers for a name
+    Forecast(ForecastArgs),
+
+    /// Export personal day numbers as an iCalendar (.ics) feed
+    Calendar(CalendarArgs),
+
+    /// Lo Shu (or Pythagorean) birth chart grid with the Arrows of Pythagoras and the planes
+    Chart(ChartArgs),
+
+    /// Date tools
+    #[command(subcommand)]
+    Dates(DatesCommand),
+}
+
+/// Subcommands of `dates`
+#[derive(Debug, Clone, Subcommand)]
+pub enum DatesCommand {
+    /// Find days whose day number matches a target
+    Find(FindDatesArgs),
+}
+
+/// Arguments for the `forecast` subcommand
+#[derive(Debug, Clone, Args)]
+pub struct ForecastArgs {
+    /// Full name (first, middle and last names)
+    #[arg(required = true)]
+    pub name: Vec<String>,
+
+    /// Birth date (e.g. 1990-03-14, 14/03/1990 or "March 14 1990")
+    #[arg(short = 'b', long = "birth-date")]
+    pub birth_date: String,
+
+    /// First year of the forecast [default: current year]
+    #[arg(long = "from")]
+    pub from: Option<i32>,
+
+    /// Last year of the forecast [default: 9 years after --from]
+    #[arg(long = "to")]
+    pub to: Option<i32>,
+}
+
+/// Arguments for the `calendar` subcommand
+#[derive(Debug, Clone, Args)]
+pub struct CalendarArgs {
+    /// Birth date (e.g. 1990-03-14, 14/03/1990 or "March 14 1990")
+    #[arg(short = 'b', long = "birth-date")]
+    pub birth_date: String,
+
+    /// First day of the calendar
+    #[arg(long = "from")]
+    pub from: String,
+
+    /// Last day of the calendar
+    #[arg(long = "to")]
+    pub to: String,
+
+    /// File to write the calendar to ("-" for stdout)
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<String>,
+
+    /// Add events for personal month and personal year transitions
+    #[arg(long = "transitions")]
+    pub transitions: bool,
+}
+
+/// Arguments for the `dates find` subcommand
+#[derive(Debug, Clone, Args)]
+pub struct FindDatesArgs {
+    /// Day number to search for (1 to 9, or 11, 22 or 33, which implies --master)
+    #[arg(short = 't', long = "target")]
+    pub target: u32,
+
+    /// First day of the search
+    #[arg(long = "from")]
+    pub from: String,
+
+    /// Last day of the search
+    #[arg(long = "to")]
+    pub to: String,
+
+    /// Match personal day numbers for this birth date instead of universal day numbers
+    #[arg(short = 'b', long = "birth-date")]
+    pub birth_date: Option<String>,
+
+    /// Keep master numbers (11, 22, 33) unreduced when matching
+    #[arg(short = 'm', long = "master")]
+    pub master: bool,
+
+    /// Only match Monday to Friday
+    #[arg(long = "weekdays")]
+    pub weekdays: bool,
+
+    /// Skip days whose total before reduction is a karmic debt number (13, 14, 16, 19)
+    #[arg(long = "no-karmic-debt")]
+    pub no_karmic_debt: bool,
+}
+
+/// Arguments for the `chart` subcommand
+#[derive(Debug, Clone, Args)]
+pub struct ChartArgs {
+    /// Name whose letter values are added to the chart
+    pub name: Vec<String>,
+
+    /// Birth date (e.g. 1990-03-14, 14/03/1990 or "March 14 1990")
+    #[arg(short = 'b', long = "birth-date")]
+    pub birth_date: String,
+
+    /// Arrangement of the digits in the grid
+    #[arg(long = "grid")]
+    #[clap(value_enum, default_value_t=GridLayout::LoShu)]
+    pub grid: GridLayout,
 }
 
 /// Color Enum for Color Choice
@@ -65,3 +340,146 @@ pub enum Decorations {
     Always,
     Never,
 }
+
+/// Output formats for word values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Colored text
+    Text,
+    /// A JSON array
+    Json,
+    /// Comma-separated values, one row per word
+    Csv,
+    /// Tab-separated values, one row per word
+    Tsv,
+    /// One compact JSON object per line, written as each word is evaluated
+    Ndjson,
+    /// The JSON data model as YAML
+    Yaml,
+    /// The JSON data model as TOML
+    Toml,
+    /// GitHub-flavored Markdown tables
+    Markdown,
+    /// A self-contained HTML page
+    Html,
+}
+
+impl OutputFormat {
+    /// Whether the format is a table with one row per word
+    pub fn is_tabular(self) -> bool {
+        matches!(
+            self,
+            OutputFormat::Csv | OutputFormat::Tsv | OutputFormat::Markdown | OutputFormat::Html
+        )
+    }
+
+    /// The formats the subcommands write
+    pub const REPORTS: [OutputFormat; 4] = [
+        OutputFormat::Text,
+        OutputFormat::Json,
+        OutputFormat::Markdown,
+        OutputFormat::Html,
+    ];
+
+    /// The name of the format as given to `--format`
+    pub fn name(self) -> String {
+        self.to_possible_value()
+            .map(|value| value.get_name().to_string())
+            .unwrap_or_default()
+    }
+
+    /// The error for a format that a subcommand cannot write, listing the `supported` ones
+    pub fn unsupported(self, command: &str, supported: &[OutputFormat]) -> String {
+        let mut names: Vec<String> = supported.iter().map(|format| format.name()).collect();
+        let last = names.pop().unwrap_or_default();
+        let choices = if names.is_empty() {
+            last
+        } else {
+            format!("{} or {last}", names.join(", "))
+        };
+        format!(
+            "The {command} command does not support --format {} (use {choices})",
+            self.name()
+        )
+    }
+}
+
+/// Layouts of the text output
+#[derive(Debug, Clone, ValueEnum)]
+pub enum WordLayout {
+    /// One line per word, followed by its letters
+    List,
+    /// A table with the letters of each word side by side and their values underneath
+    Grid,
+}
+
+/// Digit handling modes
+#[derive(Debug, Clone, ValueEnum)]
+pub enum DigitMode {
+    /// Each digit scores its face value
+    Value,
+    /// Digits are skipped
+    Ignore,
+    /// Multi-digit numbers are reduced as a whole (e.g. 2024 scores 8)
+    Whole,
+    /// Numbers are spelled out in English first (e.g. 7 becomes "seven")
+    Spell,
+}
+
+/// Handling of punctuation inside words
+#[derive(Debug, Clone, ValueEnum)]
+pub enum PunctuationRule {
+    /// Keep it as part of the word
+    Keep,
+    /// Split the word in two at it
+    Split,
+    /// Remove it and join the parts (e.g. O'Brien becomes OBrien)
+    Strip,
+}
+
+/// Built-in locales
+#[derive(Debug, Clone, ValueEnum)]
+pub enum LocaleName {
+    /// German: ß as ss, ä ö ü as ae oe ue
+    De,
+    /// Dutch: ĳ as a single letter
+    Nl,
+    /// Spanish: ch and ll as single letters, ñ as n
+    Es,
+    /// Danish: æ ø å as ae oe aa
+    Da,
+    /// Norwegian: æ ø å as ae oe aa
+    No,
+    /// Turkish: dotted and dotless i
+    Tr,
+}
+
+/// Transliteration schemes
+#[derive(Debug, Clone, ValueEnum)]
+pub enum Scheme {
+    /// ISO 9 (Cyrillic)
+    Iso9,
+    /// BGN/PCGN (Cyrillic)
+    BgnPcgn,
+    /// ELOT 743 (Greek)
+    Elot743,
+    /// IAST (Devanagari)
+    Iast,
+}
+
+/// Order of the parts of a numeric date
+#[derive(Debug, Clone, ValueEnum)]
+pub enum DateOrder {
+    Dmy,
+    Mdy,
+    Ymd,
+}
+
+/// Arrangement of the digits in the birth chart grid
+#[derive(Debug, Clone, ValueEnum)]
+pub enum GridLayout {
+    /// 4 9 2 / 3 5 7 / 8 1 6
+    LoShu,
+    /// 3 6 9 / 2 5 8 / 1 4 7
+    Pythagorean,
+}
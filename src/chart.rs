@@ -0,0 +1,334 @@
+use crossterm::style::Stylize;
+use serde_json::{json, Value};
+use std::io::Write;
+
+use crate::args::{ChartArgs, GridLayout, OutputFormat, CLI};
+use crate::date::DateParser;
+use crate::evaluate::{reduce, Evaluator};
+use crate::markup;
+use crate::style::decorate;
+
+const LO_SHU: [[u8; 3]; 3] = [[4, 9, 2], [3, 5, 7], [8, 1, 6]];
+const PYTHAGOREAN: [[u8; 3]; 3] = [[3, 6, 9], [2, 5, 8], [1, 4, 7]];
+
+/// Width of a cell in the rendered grid
+const CELL_WIDTH: usize = 7;
+
+/// A line of the grid: its digits, its name when full and its name when empty
+type Line = ([u8; 3], &'static str, &'static str);
+
+/// The Arrows of Pythagoras, the rows, columns and diagonals of the Pythagorean grid
+const ARROWS: [Line; 8] = [
+    ([1, 2, 3], "Planner", "Confusion"),
+    ([4, 5, 6], "Willpower", "Frustration"),
+    ([7, 8, 9], "Activity", "Inertia"),
+    ([1, 4, 7], "Practicality", "Disorder"),
+    ([2, 5, 8], "Emotional Balance", "Hypersensitivity"),
+    ([3, 6, 9], "Intellect", "Poor Memory"),
+    ([1, 5, 9], "Determination", "Procrastination"),
+    ([3, 5, 7], "Spirituality", "Scepticism"),
+];
+
+/// The planes of the Lo Shu grid, its rows, columns and diagonals, reported
+/// alongside the Arrows
+const PLANES: [Line; 8] = [
+    ([4, 9, 2], "Mental Plane", "Missing Mental Plane"),
+    ([3, 5, 7], "Emotional Plane", "Missing Emotional Plane"),
+    ([8, 1, 6], "Practical Plane", "Missing Practical Plane"),
+    ([4, 3, 8], "Thought Plane", "Missing Thought Plane"),
+    ([9, 5, 1], "Will Plane", "Missing Will Plane"),
+    ([2, 7, 6], "Action Plane", "Missing Action Plane"),
+    ([4, 5, 6], "Golden Plane", "Missing Golden Plane"),
+    ([2, 5, 8], "Silver Plane", "Missing Silver Plane"),
+];
+
+/// Whether a line of the chart is full or empty
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrowKind {
+    Strength,
+    Weakness,
+}
+
+/// A full or empty line of the grid: an Arrow of Pythagoras or a Lo Shu plane
+#[derive(Debug, Clone, Copy)]
+pub struct Arrow {
+    pub digits: [u8; 3],
+    pub name: &'static str,
+    pub kind: ArrowKind,
+}
+
+/// Count the digits 1 to 9 of the birth date and, optionally, the name's letter values
+pub fn digit_counts(evaluator: &Evaluator, birth_date: &str, name: &[String]) -> [u32; 10] {
+    let mut counts = [0; 10];
+    for digit in birth_date.chars().filter_map(|digit| digit.to_digit(10)) {
+        counts[digit as usize] += 1;
+    }
+    for part in name {
+        for letter in evaluator.evaluate_word(part).letters {
+            if letter.text.chars().next().is_some_and(char::is_alphabetic) {
+                counts[reduce(letter.value as u32, false) as usize] += 1;
+            }
+        }
+    }
+    // Zero has no place in the grid
+    counts[0] = 0;
+    counts
+}
+
+/// Find the full (strength) and empty (weakness) lines of the chart
+pub fn detect_arrows(counts: &[u32; 10], lines: &[Line]) -> Vec<Arrow> {
+    lines
+        .iter()
+        .filter_map(|(digits, strength, weakness)| {
+            if digits.iter().all(|digit| counts[*digit as usize] > 0) {
+                Some(Arrow {
+                    digits: *digits,
+                    name: strength,
+                    kind: ArrowKind::Strength,
+                })
+            } else if digits.iter().all(|digit| counts[*digit as usize] == 0) {
+                Some(Arrow {
+                    digits: *digits,
+                    name: weakness,
+                    kind: ArrowKind::Weakness,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Text shown in a cell: the digit repeated once per occurrence
+fn cell_text(digit: u8, count: u32) -> String {
+    if count as usize * 2 > CELL_WIDTH {
+        format!("{digit}×{count}")
+    } else {
+        digit.to_string().repeat(count as usize)
+    }
+}
+
+fn kind_name(kind: ArrowKind) -> &'static str {
+    match kind {
+        ArrowKind::Strength => "strength",
+        ArrowKind::Weakness => "weakness",
+    }
+}
+
+/// Format an arrow as `Name (1-5-9, strength)`
+fn arrow_label(arrow: &Arrow) -> String {
+    let digits: Vec<String> = arrow.digits.iter().map(|digit| digit.to_string()).collect();
+    format!(
+        "{} ({}, {})",
+        arrow.name,
+        digits.join("-"),
+        kind_name(arrow.kind)
+    )
+}
+
+fn arrow_json(arrow: &Arrow) -> Value {
+    json!({
+        "digits": arrow.digits,
+        "name": arrow.name,
+        "kind": kind_name(arrow.kind),
+    })
+}
+
+/// Run the `chart` subcommand
+pub fn run(
+    args: &CLI,
+    chart_args: &ChartArgs,
+    decorations: bool,
+    output_buffer: &mut impl Write,
+) -> Result<(), String> {
+    let birth_date = DateParser::new(args).parse(&chart_args.birth_date)?;
+    let counts = digit_counts(
+        &Evaluator::new(args)?,
+        &birth_date.format("%Y%m%d").to_string(),
+        &chart_args.name,
+    );
+    let grid = match chart_args.grid {
+        GridLayout::LoShu => LO_SHU,
+        GridLayout::Pythagorean => PYTHAGOREAN,
+    };
+    // Arrows and planes are sets of digits, whichever way the grid is laid out
+    let arrows = detect_arrows(&counts, &ARROWS);
+    let planes = detect_arrows(&counts, &PLANES);
+    let lines: Vec<(ArrowKind, String)> = arrows
+        .iter()
+        .map(|arrow| (arrow.kind, format!("Arrow of {}", arrow_label(arrow))))
+        .chain(planes.iter().map(|plane| (plane.kind, arrow_label(plane))))
+        .collect();
+    let none = "Note: No Arrows of Pythagoras in this chart.";
+
+    let format = args.output_format();
+    if matches!(format, OutputFormat::Markdown | OutputFormat::Html) {
+        let rows: Vec<Vec<String>> = grid
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|digit| cell_text(*digit, counts[*digit as usize]))
+                    .collect()
+            })
+            .collect();
+        let lines: Vec<String> = lines.into_iter().map(|(_, line)| line).collect();
+        let note = (arrows.is_empty() && !args.quiet).then_some(none);
+        if format == OutputFormat::Markdown {
+            write!(
+                output_buffer,
+                "{}",
+                markup::markdown_table(&["", "", ""], &rows)
+            )
+            .unwrap();
+            for line in &lines {
+                writeln!(output_buffer, "\n{}", markup::escape_markdown(line)).unwrap();
+            }
+            if let Some(note) = note {
+                writeln!(output_buffer, "\n*{note}*").unwrap();
+            }
+        } else {
+            let page = markup::html_table("numeracalc chart", &[], &rows, &lines, note);
+            write!(output_buffer, "{page}").unwrap();
+        }
+        return Ok(());
+    }
+    if format != OutputFormat::Json && format != OutputFormat::Text {
+        return Err(format.unsupported("chart", &OutputFormat::REPORTS));
+    }
+
+    if format == OutputFormat::Json {
+        let json_output = json!({
+            "birth_date": birth_date.format("%Y-%m-%d").to_string(),
+            "counts": (1..=9)
+                .map(|digit| (digit.to_string(), json!(counts[digit])))
+                .collect::<serde_json::Map<String, Value>>(),
+            "grid": grid,
+            "arrows": arrows.iter().map(arrow_json).collect::<Vec<Value>>(),
+            "planes": planes.iter().map(arrow_json).collect::<Vec<Value>>(),
+        });
+        let json_string = serde_json::to_string_pretty(&json_output)
+            .expect("error: Failed to serialize output to json");
+        writeln!(output_buffer, "{}", json_string).unwrap();
+        return Ok(());
+    }
+
+    if args.less || args.raw {
+        for row in &grid {
+            let cells: Vec<String> = row
+                .iter()
+                .map(|digit| cell_text(*digit, counts[*digit as usize]))
+                .collect();
+            writeln!(output_buffer, "{}", cells.join("|")).unwrap();
+        }
+        for arrow in arrows.iter().chain(&planes) {
+            writeln!(output_buffer, "{}", arrow_label(arrow)).unwrap();
+        }
+        return Ok(());
+    }
+
+    let border = |left: &str, middle: &str, right: &str| {
+        format!(
+            "{left}{}{right}",
+            vec!["─".repeat(CELL_WIDTH); 3].join(middle)
+        )
+    };
+    writeln!(
+        output_buffer,
+        "{}",
+        decorate(border("┌", "┬", "┐").green(), decorations)
+    )
+    .unwrap();
+    for (index, row) in grid.iter().enumerate() {
+        write!(output_buffer, "{}", decorate("│".green(), decorations)).unwrap();
+        for digit in row {
+            let count = counts[*digit as usize];
+            let text = format!("{:^CELL_WIDTH$}", cell_text(*digit, count));
+            if count > 0 {
+                write!(output_buffer, "{}", decorate(text.blue(), decorations)).unwrap();
+            } else {
+                write!(output_buffer, "{}", text.dark_grey()).unwrap();
+            }
+            write!(output_buffer, "{}", decorate("│".green(), decorations)).unwrap();
+        }
+        writeln!(output_buffer).unwrap();
+        if index < grid.len() - 1 {
+            writeln!(
+                output_buffer,
+                "{}",
+                decorate(border("├", "┼", "┤").green(), decorations)
+            )
+            .unwrap();
+        }
+    }
+    writeln!(
+        output_buffer,
+        "{}",
+        decorate(border("└", "┴", "┘").green(), decorations)
+    )
+    .unwrap();
+
+    for (kind, line) in lines {
+        match kind {
+            ArrowKind::Strength => writeln!(output_buffer, "{}", line.dark_cyan()).unwrap(),
+            ArrowKind::Weakness => writeln!(output_buffer, "{}", line.dark_red()).unwrap(),
+        }
+    }
+    if arrows.is_empty() && !args.quiet {
+        let note = none.dark_yellow();
+        if decorations {
+            writeln!(output_buffer, "{}", note.italic()).unwrap();
+        } else {
+            writeln!(output_buffer, "{}", note).unwrap();
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use crate::args::Command;
+
+    /// The JSON output of `chart` for 1990-03-14 in a grid layout
+    fn chart_json(grid: &str) -> Value {
+        let args = CLI::parse_from([
+            "numeracalc",
+            "--json",
+            "chart",
+            "-b",
+            "1990-03-14",
+            "--grid",
+            grid,
+        ]);
+        let Some(Command::Chart(chart_args)) = &args.command else {
+            panic!("not a chart command");
+        };
+        let mut output = vec![];
+        run(&args, chart_args, false, &mut output).unwrap();
+        serde_json::from_slice(&output).unwrap()
+    }
+
+    #[test]
+    fn arrows_do_not_depend_on_the_layout() {
+        // The digits 1, 1, 3, 4, 9 and 9 leave only the 2-5-8 line empty
+        let arrows = json!([
+            { "digits": [2, 5, 8], "name": "Hypersensitivity", "kind": "weakness" },
+        ]);
+        for grid in ["lo-shu", "pythagorean"] {
+            let chart = chart_json(grid);
+            assert_eq!(chart["arrows"], arrows, "{grid}");
+            assert_eq!(
+                chart["planes"],
+                json!([
+                    { "digits": [2, 7, 6], "name": "Missing Action Plane", "kind": "weakness" },
+                    { "digits": [2, 5, 8], "name": "Missing Silver Plane", "kind": "weakness" },
+                ]),
+                "{grid}"
+            );
+        }
+        assert_eq!(chart_json("lo-shu")["grid"], json!(LO_SHU));
+        assert_eq!(chart_json("pythagorean")["grid"], json!(PYTHAGOREAN));
+    }
+}
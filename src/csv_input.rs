@@ -0,0 +1,153 @@
+use std::io::{self, Read, Write};
+
+use crate::args::CLI;
+use crate::evaluate::{quote, reduce, Evaluator};
+use crate::input::expand_inputs;
+use crate::output::report_unknown_chars;
+use crate::tokenize::Tokenizer;
+
+/// Columns appended to every row
+const EXTRA_COLUMNS: [&str; 4] = ["value", "reduced", "soul_urge", "personality"];
+
+/// Values appended to a row for the words of its column
+fn row_values(evaluator: &Evaluator, words: &[String]) -> [u64; 4] {
+    let (mut value, mut vowels, mut consonants) = (0, 0, 0);
+    for word in words {
+        let word_value = evaluator.evaluate_word(word);
+        value += word_value.value;
+        vowels += word_value.vowel_value();
+        consonants += word_value.consonant_value();
+    }
+    [
+        value,
+        reduce(value, true).into(),
+        reduce(vowels, true).into(),
+        reduce(consonants, true).into(),
+    ]
+}
+
+/// Find the index of the `--column` in the header (or by its number)
+fn column_index(column: &str, header: Option<&csv::StringRecord>) -> Result<usize, String> {
+    if let Some(index) = header.and_then(|header| header.iter().position(|name| name == column)) {
+        return Ok(index);
+    }
+    match (column.parse::<usize>(), header) {
+        (Ok(number), Some(header)) if number > header.len() => Err(format!(
+            "No column {number} in the CSV header, which has {} columns",
+            header.len()
+        )),
+        (Ok(number), _) if number > 0 => Ok(number - 1),
+        (_, Some(_)) => Err(format!("No column named {column:?} in the CSV header")),
+        (_, None) => Err(format!(
+            "The column must be a number from 1 with --no-header, not {column:?}"
+        )),
+    }
+}
+
+/// Augment the rows of one CSV source, writing the header only if `write_header` is set
+fn process(
+    args: &CLI,
+    evaluator: &Evaluator,
+    tokenizer: &Tokenizer,
+    source: impl Read,
+    name: &str,
+    writer: &mut csv::Writer<impl Write>,
+    write_header: bool,
+) -> Result<(), String> {
+    let delimiter = u8::try_from(args.csv_delimiter)
+        .map_err(|_| "The CSV delimiter must be a single-byte character".to_string())?;
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(!args.no_header)
+        .flexible(true)
+        .from_reader(source);
+    let column = args.column.as_deref().unwrap_or("1");
+
+    let header = if args.no_header {
+        None
+    } else {
+        Some(
+            reader
+                .headers()
+                .map_err(|e| format!("Failed to read {name}: {e}"))?
+                .clone(),
+        )
+    };
+    let index = column_index(column, header.as_ref())?;
+    if let (Some(header), true) = (&header, write_header) {
+        let mut header = header.clone();
+        for extra in EXTRA_COLUMNS {
+            header.push_field(extra);
+        }
+        writer
+            .write_record(&header)
+            .map_err(|e| format!("Failed to write CSV: {e}"))?;
+    }
+
+    for record in reader.records() {
+        let mut record = record.map_err(|e| format!("Failed to read {name}: {e}"))?;
+        let line = record.position().map_or(0, |position| position.line());
+        let text = record
+            .get(index)
+            .ok_or_else(|| format!("No column {} on line {line} of {name}", index + 1))?;
+        // Split and check the cell like any other words
+        let words = tokenizer.split(text);
+        if report_unknown_chars(args, evaluator, &words).is_none() {
+            return Err(format!(
+                "Unscored characters in {} on line {line} of {name}",
+                quote(text)
+            ));
+        }
+        for value in row_values(evaluator, &words) {
+            record.push_field(&value.to_string());
+        }
+        writer
+            .write_record(&record)
+            .map_err(|e| format!("Failed to write CSV: {e}"))?;
+    }
+    Ok(())
+}
+
+/// Read CSV from the `--input` files (or stdin) and write the augmented rows
+pub fn run(
+    args: &CLI,
+    evaluator: &Evaluator,
+    tokenizer: &Tokenizer,
+    output_buffer: &mut impl Write,
+) -> Result<(), String> {
+    let delimiter = u8::try_from(args.csv_delimiter)
+        .map_err(|_| "The CSV delimiter must be a single-byte character".to_string())?;
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_writer(output_buffer);
+
+    if args.input.is_empty() {
+        process(
+            args,
+            evaluator,
+            tokenizer,
+            io::stdin().lock(),
+            "<stdin>",
+            &mut writer,
+            true,
+        )?;
+    } else {
+        for (index, file) in expand_inputs(&args.input)?.iter().enumerate() {
+            let source =
+                std::fs::File::open(file).map_err(|e| format!("Failed to open {file:?}: {e}"))?;
+            process(
+                args,
+                evaluator,
+                tokenizer,
+                source,
+                file,
+                &mut writer,
+                index == 0,
+            )?;
+        }
+    }
+    writer
+        .flush()
+        .map_err(|e| format!("Failed to write CSV: {e}"))
+}
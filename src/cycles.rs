@@ -0,0 +1,105 @@
+use chrono::{Datelike, NaiveDate};
+
+use crate::evaluate::reduce;
+
+/// Personal Year number: birth month and day added to the calendar year
+pub fn personal_year(birth_date: NaiveDate, year: i32) -> u32 {
+    let year = reduce(year.unsigned_abs(), false);
+    reduce(
+        reduce(birth_date.month(), true) + reduce(birth_date.day(), true) + year,
+        true,
+    )
+}
+
+/// Personal Month number: Personal Year added to the calendar month
+pub fn personal_month(birth_date: NaiveDate, date: NaiveDate) -> u32 {
+    reduce(
+        reduce(personal_year(birth_date, date.year()), false) + date.month(),
+        true,
+    )
+}
+
+/// Personal Day total before reduction: Personal Month added to the day of the month
+pub fn personal_day_total(birth_date: NaiveDate, date: NaiveDate) -> u32 {
+    reduce(personal_month(birth_date, date), false) + date.day()
+}
+
+/// Personal Day number
+pub fn personal_day(birth_date: NaiveDate, date: NaiveDate) -> u32 {
+    reduce(personal_day_total(birth_date, date), true)
+}
+
+/// Universal Day total before reduction: the sum of every digit of the date
+pub fn universal_day_total(date: NaiveDate) -> u32 {
+    date.format("%Y%m%d")
+        .to_string()
+        .chars()
+        .filter_map(|digit| digit.to_digit(10))
+        .sum()
+}
+
+/// Whether a total is a karmic debt number (13, 14, 16 or 19)
+pub fn is_karmic_debt(total: u32) -> bool {
+    matches!(total, 13 | 14 | 16 | 19)
+}
+
+/// Short meaning of a cycle number
+pub fn meaning(number: u32) -> &'static str {
+    match number {
+        1 => "New beginnings",
+        2 => "Cooperation",
+        3 => "Creativity",
+        4 => "Hard work",
+        5 => "Change",
+        6 => "Responsibility",
+        7 => "Reflection",
+        8 => "Achievement",
+        9 => "Completion",
+        11 => "Inspiration",
+        22 => "Master building",
+        33 => "Compassion",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn personal_cycles() {
+        let birth_date = date(1990, 3, 14);
+        // 3 + (1 + 4) + (2 + 0 + 2 + 4) = 16, which reduces to 7
+        assert_eq!(personal_year(birth_date, 2024), 7);
+        // 7 + 6 = 13, which reduces to 4
+        assert_eq!(personal_month(birth_date, date(2024, 6, 20)), 4);
+        // 4 + 20 = 24, which reduces to 6
+        assert_eq!(personal_day_total(birth_date, date(2024, 6, 20)), 24);
+        assert_eq!(personal_day(birth_date, date(2024, 6, 20)), 6);
+    }
+
+    #[test]
+    fn master_numbers_are_kept() {
+        let birth_date = date(1990, 3, 14);
+        // 3 + 5 + (2 + 0 + 2 + 8 = 12, reduced to 3) = 11
+        assert_eq!(personal_year(birth_date, 2028), 11);
+        // The master Personal Year is reduced before the month is added: 2 + 1
+        assert_eq!(personal_month(birth_date, date(2028, 1, 1)), 3);
+        // 4 + 18 = 22
+        assert_eq!(personal_day(birth_date, date(2024, 6, 18)), 22);
+    }
+
+    #[test]
+    fn universal_days() {
+        assert_eq!(universal_day_total(date(2024, 6, 18)), 23);
+        assert_eq!(reduce(universal_day_total(date(2024, 6, 18)), true), 5);
+        assert_eq!(universal_day_total(date(2024, 1, 2)), 11);
+        assert_eq!(reduce(universal_day_total(date(2024, 1, 2)), true), 11);
+        assert!(is_karmic_debt(13) && is_karmic_debt(19));
+        assert!(!is_karmic_debt(15));
+    }
+}
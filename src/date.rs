@@ -0,0 +1,306 @@
+use chrono::{Datelike, NaiveDate};
+
+use crate::args::{DateOrder, CLI};
+
+/// Month names (full or abbreviated) in English, German, French and Spanish
+const MONTH_NAMES: [&[&str]; 12] = [
+    &[
+        "january", "jan", "januar", "jän", "janvier", "janv", "enero", "ene",
+    ],
+    &[
+        "february", "feb", "februar", "février", "févr", "fevrier", "fevr", "febrero",
+    ],
+    &["march", "mar", "märz", "mär", "maerz", "mars", "marzo"],
+    &["april", "apr", "avril", "avr", "abril", "abr"],
+    &["may", "mai", "mayo"],
+    &["june", "jun", "juni", "juin", "junio"],
+    &["july", "jul", "juli", "juillet", "juil", "julio"],
+    &["august", "aug", "août", "aout", "agosto", "ago"],
+    &[
+        "september",
+        "sep",
+        "sept",
+        "septembre",
+        "septiembre",
+        "setiembre",
+    ],
+    &["october", "oct", "oktober", "okt", "octobre", "octubre"],
+    &["november", "nov", "novembre", "noviembre"],
+    &[
+        "december",
+        "dec",
+        "dezember",
+        "dez",
+        "décembre",
+        "decembre",
+        "diciembre",
+        "dic",
+    ],
+];
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// A piece of a date: either a number (with its digit count) or a month name
+#[derive(Debug, Clone, Copy)]
+enum Token {
+    Number(u32, usize),
+    Month(u32),
+}
+
+/// Parses dates written in the formats users commonly type
+#[derive(Debug, Clone)]
+pub struct DateParser {
+    format: Option<String>,
+    order: Option<DateOrder>,
+}
+
+impl DateParser {
+    /// Create a parser using the `--date-format` and `--date-order` options
+    pub fn new(args: &CLI) -> DateParser {
+        DateParser {
+            format: args.date_format.clone(),
+            order: args.date_order.clone(),
+        }
+    }
+
+    /// Parse a date such as `1990-03-14`, `14/03/1990`, `March 14 1990` or `14 Mär 1990`
+    pub fn parse(&self, input: &str) -> Result<NaiveDate, String> {
+        let input = input.trim();
+        if let Some(format) = &self.format {
+            return NaiveDate::parse_from_str(input, format)
+                .map_err(|e| format!("Invalid date {input:?} for format {format:?}: {e}"));
+        }
+
+        let tokens = tokenize(input)?;
+        let (year, month, day) = match tokens.as_slice() {
+            // Compact YYYYMMDD
+            [Token::Number(number, 8)] => (number / 10000, number / 100 % 100, number % 100),
+            [Token::Month(month), Token::Number(day, ..=2), Token::Number(year, 4)]
+            | [Token::Number(day, ..=2), Token::Month(month), Token::Number(year, 4)]
+            | [Token::Number(year, 4), Token::Month(month), Token::Number(day, ..=2)] => {
+                (*year, *month, *day)
+            }
+            [Token::Number(first, first_len), Token::Number(second, ..=2), Token::Number(third, third_len)] =>
+            {
+                match (*first_len, *third_len, &self.order) {
+                    (4, ..=2, _) => (*first, *second, *third),
+                    (..=2, 4, Some(DateOrder::Ymd)) => {
+                        return Err(format!(
+                            "Invalid date {input:?}: --date-order ymd expects the year first"
+                        ))
+                    }
+                    (..=2, 4, _) => {
+                        let (day, month) = self.resolve_day_month(input, *first, *second)?;
+                        (*third, month, day)
+                    }
+                    // Two-digit years need the order to tell them from days
+                    (..=2, ..=2, Some(DateOrder::Ymd)) => (full_year(*first), *second, *third),
+                    (..=2, ..=2, Some(DateOrder::Dmy)) => (full_year(*third), *second, *first),
+                    (..=2, ..=2, Some(DateOrder::Mdy)) => (full_year(*third), *first, *second),
+                    _ => return Err(unrecognized(input)),
+                }
+            }
+            _ => return Err(unrecognized(input)),
+        };
+
+        if !(1..=12).contains(&month) {
+            return Err(format!("Invalid date {input:?}: there is no month {month}"));
+        }
+        NaiveDate::from_ymd_opt(year as i32, month, day).ok_or_else(|| {
+            format!(
+                "Invalid date {input:?}: {} {year} has no day {day}",
+                MONTHS[month as usize - 1]
+            )
+        })
+    }
+
+    /// Decide which of two leading numbers is the day and which is the month
+    fn resolve_day_month(
+        &self,
+        input: &str,
+        first: u32,
+        second: u32,
+    ) -> Result<(u32, u32), String> {
+        match self.order {
+            Some(DateOrder::Dmy) => Ok((first, second)),
+            Some(DateOrder::Mdy) => Ok((second, first)),
+            _ if first > 12 || first == second => Ok((first, second)),
+            _ if second > 12 => Ok((second, first)),
+            _ => Err(format!(
+                "Ambiguous date {input:?}: could be day/month or month/day \
+                (use --date-order dmy or --date-order mdy)"
+            )),
+        }
+    }
+}
+
+/// Expand a two-digit year to the latest year that is not in the future
+fn full_year(year: u32) -> u32 {
+    let current = chrono::Local::now().year() as u32;
+    let century = current - current % 100;
+    if century + year > current {
+        century - 100 + year
+    } else {
+        century + year
+    }
+}
+
+/// Split a date into numbers and month names
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    input
+        .split(|character: char| !character.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            if token.chars().all(|character| character.is_ascii_digit()) {
+                token
+                    .parse()
+                    .map(|number| Token::Number(number, token.len()))
+                    .map_err(|_| unrecognized(input))
+            } else {
+                month_number(token)
+                    .map(Token::Month)
+                    .ok_or_else(|| format!("Invalid date {input:?}: unknown month {token:?}"))
+            }
+        })
+        .collect()
+}
+
+/// Look up a month by name, ignoring case
+fn month_number(name: &str) -> Option<u32> {
+    let name = name.to_lowercase();
+    MONTH_NAMES
+        .iter()
+        .position(|names| names.contains(&name.as_str()))
+        .map(|index| index as u32 + 1)
+}
+
+fn unrecognized(input: &str) -> String {
+    format!(
+        "Unrecognized date {input:?} (expected e.g. 1990-03-14, 14/03/1990 or March 14 1990; \
+        use --date-format for other formats)"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parser(order: Option<DateOrder>) -> DateParser {
+        DateParser {
+            format: None,
+            order,
+        }
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn parses_common_formats() {
+        let parser = parser(None);
+        for input in [
+            "1990-03-14",
+            "1990/03/14",
+            "19900314",
+            "14/03/1990",
+            "14.03.1990",
+            "03/14/1990",
+            "March 14 1990",
+            "march 14, 1990",
+            "14 Mar 1990",
+            "14 Mär 1990",
+            "14 mars 1990",
+            "14 marzo 1990",
+            "1990 March 14",
+        ] {
+            assert_eq!(parser.parse(input), Ok(date(1990, 3, 14)), "{input}");
+        }
+    }
+
+    #[test]
+    fn rejects_ambiguous_day_and_month() {
+        let error = parser(None).parse("03/04/1990").unwrap_err();
+        assert!(error.starts_with("Ambiguous date"), "{error}");
+        // Equal day and month are not ambiguous
+        assert_eq!(parser(None).parse("04/04/1990"), Ok(date(1990, 4, 4)));
+    }
+
+    #[test]
+    fn date_order_settles_day_and_month() {
+        let input = "03/04/1990";
+        assert_eq!(
+            parser(Some(DateOrder::Dmy)).parse(input),
+            Ok(date(1990, 4, 3))
+        );
+        assert_eq!(
+            parser(Some(DateOrder::Mdy)).parse(input),
+            Ok(date(1990, 3, 4))
+        );
+        assert!(parser(Some(DateOrder::Ymd)).parse(input).is_err());
+    }
+
+    #[test]
+    fn date_order_allows_two_digit_years() {
+        assert_eq!(
+            parser(Some(DateOrder::Ymd)).parse("90/03/14"),
+            Ok(date(1990, 3, 14))
+        );
+        assert_eq!(
+            parser(Some(DateOrder::Dmy)).parse("14.03.90"),
+            Ok(date(1990, 3, 14))
+        );
+        assert_eq!(
+            parser(Some(DateOrder::Mdy)).parse("03/14/90"),
+            Ok(date(1990, 3, 14))
+        );
+        assert_eq!(
+            parser(Some(DateOrder::Ymd)).parse("05-03-14"),
+            Ok(date(2005, 3, 14))
+        );
+        assert!(parser(None).parse("90/03/14").is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_days_and_months() {
+        let parser = parser(None);
+        assert_eq!(
+            parser.parse("1990-02-30"),
+            Err("Invalid date \"1990-02-30\": February 1990 has no day 30".to_string())
+        );
+        assert_eq!(
+            parser.parse("1990-13-01"),
+            Err("Invalid date \"1990-13-01\": there is no month 13".to_string())
+        );
+        assert!(parser.parse("February 29 1990").is_err());
+        assert_eq!(parser.parse("February 29 1992"), Ok(date(1992, 2, 29)));
+        assert!(parser
+            .parse("14 Foo 1990")
+            .unwrap_err()
+            .contains("unknown month"));
+        assert!(parser.parse("yesterday").is_err());
+    }
+
+    #[test]
+    fn date_format_overrides_detection() {
+        let parser = DateParser {
+            format: Some("%d.%m.%Y".to_string()),
+            order: Some(DateOrder::Mdy),
+        };
+        assert_eq!(parser.parse("03.04.1990"), Ok(date(1990, 4, 3)));
+        assert!(parser.parse("1990-04-03").is_err());
+    }
+}
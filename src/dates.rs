@@ -0,0 +1,175 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use crossterm::style::Stylize;
+use prettytable::{format, Cell, Row, Table};
+use serde_json::{json, Value};
+use std::io::Write;
+
+use crate::args::{FindDatesArgs, OutputFormat, CLI};
+use crate::cycles::{is_karmic_debt, meaning, personal_day_total, universal_day_total};
+use crate::date::DateParser;
+use crate::evaluate::reduce;
+use crate::markup;
+use crate::style::decorate;
+
+/// A day matching the search target
+#[derive(Debug, Clone)]
+pub struct MatchedDate {
+    pub date: NaiveDate,
+    pub total: u32,
+    pub number: u32,
+}
+
+/// Search `from` to `to` (inclusive) for days matching the target number
+///
+/// A master number target (11, 22 or 33) implies `--master`, since it is only
+/// kept unreduced then.
+pub fn find_dates(
+    find_args: &FindDatesArgs,
+    birth_date: Option<NaiveDate>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<MatchedDate> {
+    let master = find_args.master || matches!(find_args.target, 11 | 22 | 33);
+    from.iter_days()
+        .take_while(|date| *date <= to)
+        .filter(|date| {
+            !find_args.weekdays || !matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+        })
+        .map(|date| {
+            let total = match birth_date {
+                Some(birth_date) => personal_day_total(birth_date, date),
+                None => universal_day_total(date),
+            };
+            MatchedDate {
+                date,
+                total,
+                number: reduce(total, master),
+            }
+        })
+        .filter(|matched| matched.number == find_args.target)
+        .filter(|matched| !find_args.no_karmic_debt || !is_karmic_debt(matched.total))
+        .collect()
+}
+
+/// Run the `dates find` subcommand
+pub fn run_find(
+    args: &CLI,
+    find_args: &FindDatesArgs,
+    decorations: bool,
+    output_buffer: &mut impl Write,
+) -> Result<(), String> {
+    if !matches!(find_args.target, 1..=9 | 11 | 22 | 33) {
+        return Err(format!(
+            "Invalid target {}: day numbers are 1 to 9, or the master numbers 11, 22 and 33",
+            find_args.target
+        ));
+    }
+    let date_parser = DateParser::new(args);
+    let birth_date = find_args
+        .birth_date
+        .as_deref()
+        .map(|date| date_parser.parse(date))
+        .transpose()?;
+    let from = date_parser.parse(&find_args.from)?;
+    let to = date_parser.parse(&find_args.to)?;
+    if to < from {
+        return Err(format!(
+            "Invalid date range: {} to {}",
+            find_args.from, find_args.to
+        ));
+    }
+
+    let matches = find_dates(find_args, birth_date, from, to);
+
+    let format = args.output_format();
+    let kind = if birth_date.is_some() {
+        "personal"
+    } else {
+        "universal"
+    };
+    let note = format!("Note: Found {} {kind} day(s).", matches.len());
+    let note = (!args.quiet).then_some(note.as_str());
+    let titles = ["Date", "Weekday", "Total", "Number", "Meaning"];
+    let rows: Vec<Vec<String>> = matches
+        .iter()
+        .map(|matched| {
+            vec![
+                matched.date.format("%Y-%m-%d").to_string(),
+                matched.date.format("%A").to_string(),
+                matched.total.to_string(),
+                matched.number.to_string(),
+                meaning(matched.number).to_string(),
+            ]
+        })
+        .collect();
+
+    if format == OutputFormat::Markdown {
+        write!(output_buffer, "{}", markup::markdown_table(&titles, &rows)).unwrap();
+        if let Some(note) = note {
+            writeln!(output_buffer, "\n*{note}*").unwrap();
+        }
+    } else if format == OutputFormat::Html {
+        let page = markup::html_table("numeracalc dates", &titles, &rows, &[], note);
+        write!(output_buffer, "{page}").unwrap();
+    } else if format == OutputFormat::Json {
+        let json_output: Vec<Value> = matches
+            .iter()
+            .map(|matched| {
+                json!({
+                    "date": matched.date.format("%Y-%m-%d").to_string(),
+                    "weekday": matched.date.format("%A").to_string(),
+                    "total": matched.total,
+                    "number": matched.number,
+                })
+            })
+            .collect();
+        let json_string = serde_json::to_string_pretty(&json_output)
+            .expect("error: Failed to serialize output to json");
+        writeln!(output_buffer, "{}", json_string).unwrap();
+    } else if format != OutputFormat::Text {
+        return Err(format.unsupported("dates find", &OutputFormat::REPORTS));
+    } else if args.less || args.raw {
+        for matched in &matches {
+            writeln!(output_buffer, "{}", matched.date.format("%Y-%m-%d")).unwrap();
+        }
+    } else {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+        table.set_titles(Row::new(
+            ["DATE", "WEEKDAY", "TOTAL", "NUMBER", "MEANING"]
+                .iter()
+                .map(|title| Cell::new(&format!("{}", decorate(title.green(), decorations))))
+                .collect(),
+        ));
+        for matched in &matches {
+            table.add_row(Row::new(vec![
+                Cell::new(&format!(
+                    "{}",
+                    decorate(
+                        matched.date.format("%Y-%m-%d").to_string().dark_cyan(),
+                        decorations
+                    )
+                )),
+                Cell::new(&matched.date.format("%A").to_string()),
+                Cell::new(&matched.total.to_string()),
+                Cell::new(&format!(
+                    "{}",
+                    decorate(matched.number.to_string().blue(), decorations)
+                )),
+                Cell::new(meaning(matched.number)),
+            ]));
+        }
+        table
+            .print(output_buffer)
+            .expect("error: Failed to print table");
+        if let Some(note) = note {
+            let note = note.dark_yellow();
+            if decorations {
+                writeln!(output_buffer, "{}", note.italic()).unwrap();
+            } else {
+                writeln!(output_buffer, "{}", note).unwrap();
+            }
+        }
+    }
+    Ok(())
+}
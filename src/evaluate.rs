@@ -1,7 +1,16 @@
+use std::ops::Range;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::args::{DigitMode, Scheme, CLI};
+use crate::data::VALUE_TABLE;
+use crate::locale::{LetterRule, Locale};
+use crate::transliterate::transliterate;
+
 pub fn evaluate(letter: char) -> u8 {
     let binding = letter.to_lowercase().to_string();
-    let letter = binding.chars().nth(0).unwrap();
-    return match letter {
+    let letter = binding.chars().next().unwrap();
+    match letter {
         '0' => 0,
         '1' => 1,
         '2' => 2,
@@ -41,5 +50,483 @@ pub fn evaluate(letter: char) -> u8 {
         'z' => 7,
 
         _ => 0,
-    };
+    }
+}
+
+/// Reduce a value to a single digit by repeatedly summing its digits,
+/// stopping early at the master numbers (11, 22, 33) if `keep_master` is set
+pub fn reduce(value: impl Into<u64>, keep_master: bool) -> u32 {
+    let mut value = value.into();
+    while value > 9 {
+        if keep_master && matches!(value, 11 | 22 | 33) {
+            break;
+        }
+        let mut sum = 0;
+        while value > 0 {
+            sum += value % 10;
+            value /= 10;
+        }
+        value = sum;
+    }
+    // A single digit or a master number
+    value as u32
+}
+
+/// A user-visible letter of a word and its value
+#[derive(Debug, Clone)]
+pub struct LetterValue {
+    /// The letter as written in the input
+    pub text: String,
+    /// Byte range of the letter in the word (or in its transliteration)
+    pub span: Range<usize>,
+    pub value: u8,
+    /// Whether every character of the letter is in the table
+    pub scored: bool,
+}
+
+/// A word with its total value and per-letter breakdown
+#[derive(Debug, Clone)]
+pub struct WordValue {
+    /// The word in Latin script, if it was transliterated
+    pub transliterated: Option<String>,
+    pub letters: Vec<LetterValue>,
+    pub value: u64,
+}
+
+impl WordValue {
+    /// Sum of the values of the vowels (the Soul Urge before reduction)
+    pub fn vowel_value(&self) -> u64 {
+        self.letters
+            .iter()
+            .filter(|letter| is_vowel(&letter.text))
+            .map(|letter| letter.value as u64)
+            .sum()
+    }
+
+    /// Sum of the values of the consonants (the Personality before reduction)
+    pub fn consonant_value(&self) -> u64 {
+        self.letters
+            .iter()
+            .filter(|letter| letter.text.chars().next().is_some_and(char::is_alphabetic))
+            .filter(|letter| !is_vowel(&letter.text))
+            .map(|letter| letter.value as u64)
+            .sum()
+    }
+}
+
+/// A character that has no value in the table
+#[derive(Debug, Clone)]
+pub struct UnknownChar {
+    pub word: String,
+    pub character: String,
+    /// Position of the letter in the word, starting at 1
+    pub position: usize,
+}
+
+/// Evaluates whole words according to the chosen options
+#[derive(Debug, Clone)]
+pub struct Evaluator {
+    /// Score accented letters like their base letters
+    pub normalize: bool,
+    /// Transliterate words to Latin script first
+    pub transliteration: Option<Scheme>,
+    /// How digits in words are scored
+    pub digits: DigitMode,
+    /// Letters of the chosen locale
+    pub locale: Option<Locale>,
+}
+
+impl Evaluator {
+    /// Create an evaluator from the command line options
+    pub fn new(args: &CLI) -> Result<Evaluator, String> {
+        Ok(Evaluator {
+            normalize: !args.no_normalize,
+            transliteration: args.transliterate.clone(),
+            digits: args.digits.clone(),
+            locale: Locale::new(args)?,
+        })
+    }
+
+    /// Value of a single letter, decomposing it (NFKD) and ignoring combining
+    /// marks when normalization is enabled, and whether it could be scored
+    fn letter_value(&self, letter: &str) -> (u8, bool) {
+        let characters: Vec<char> = if self.normalize {
+            letter
+                .nfkd()
+                .filter(|character| !is_combining_mark(*character))
+                .collect()
+        } else {
+            letter.chars().collect()
+        };
+        (
+            characters
+                .iter()
+                .map(|character| evaluate(*character))
+                .sum(),
+            !characters.is_empty() && characters.iter().all(|character| is_scored(*character)),
+        )
+    }
+
+    /// Split a word into letters and calculate their values
+    pub fn evaluate_word(&self, word: &str) -> WordValue {
+        let transliterated = self
+            .transliteration
+            .as_ref()
+            .map(|scheme| transliterate(scheme, word));
+        let text = transliterated.as_deref().unwrap_or(word);
+        // Split the word into letters (extended grapheme clusters, locale letters
+        // and digit runs), with a value for the ones not looked up in the table
+        let mut units: Vec<(Range<usize>, String, Option<u8>)> = vec![];
+        let mut position = 0;
+        while position < text.len() {
+            let rest = &text[position..];
+            if let Some((length, rule)) = self
+                .locale
+                .as_ref()
+                .and_then(|locale| locale.match_letter(rest))
+            {
+                let value = match rule {
+                    LetterRule::Spelling(spelling) => spelling
+                        .chars()
+                        .map(|letter| self.letter_value(&letter.to_string()).0)
+                        .sum(),
+                    LetterRule::Value(value) => *value,
+                };
+                let span = position..position + length;
+                units.push((span, rest[..length].to_string(), Some(value)));
+                position += length;
+                continue;
+            }
+            if rest.starts_with(|character: char| character.is_ascii_digit())
+                && !matches!(self.digits, DigitMode::Value)
+            {
+                let length = rest
+                    .find(|character: char| !character.is_ascii_digit())
+                    .unwrap_or(rest.len());
+                let number = &rest[..length];
+                let span = position..position + length;
+                match self.digits {
+                    DigitMode::Value | DigitMode::Ignore => {}
+                    DigitMode::Whole => {
+                        let sum: u32 = number.chars().filter_map(|digit| digit.to_digit(10)).sum();
+                        units.push((span, number.to_string(), Some(reduce(sum, false) as u8)));
+                    }
+                    DigitMode::Spell => units.extend(
+                        spell_number(number)
+                            .chars()
+                            .map(|letter| (span.clone(), letter.to_string(), None)),
+                    ),
+                }
+                position += length;
+                continue;
+            }
+            let grapheme = rest.graphemes(true).next().unwrap_or(rest);
+            units.push((
+                position..position + grapheme.len(),
+                grapheme.to_string(),
+                None,
+            ));
+            position += grapheme.len();
+        }
+        let letters: Vec<LetterValue> = units
+            .into_iter()
+            .map(|(span, text, value)| {
+                let (value, scored) = match value {
+                    Some(value) => (value, true),
+                    None => self.letter_value(&text),
+                };
+                LetterValue {
+                    text,
+                    span,
+                    value,
+                    scored,
+                }
+            })
+            .collect();
+        WordValue {
+            transliterated,
+            value: letters.iter().map(|letter| letter.value as u64).sum(),
+            letters,
+        }
+    }
+
+    /// Find every letter of the words that has no value in the table
+    pub fn unknown_chars(&self, words: &[String]) -> Vec<UnknownChar> {
+        let mut unknown_chars = vec![];
+        for word in words {
+            for (index, letter) in self.evaluate_word(word).letters.into_iter().enumerate() {
+                if !letter.scored {
+                    unknown_chars.push(UnknownChar {
+                        word: word.clone(),
+                        character: letter.text,
+                        position: index + 1,
+                    });
+                }
+            }
+        }
+        unknown_chars
+    }
+}
+
+/// Whether a letter is a vowel (a, e, i, o or u, including accented forms)
+pub fn is_vowel(letter: &str) -> bool {
+    letter
+        .nfkd()
+        .next()
+        .is_some_and(|base| matches!(base.to_ascii_lowercase(), 'a' | 'e' | 'i' | 'o' | 'u'))
+}
+
+/// Whether a character has a value in the table
+pub fn is_scored(letter: char) -> bool {
+    letter
+        .to_lowercase()
+        .next()
+        .is_some_and(|letter| VALUE_TABLE.contains_key(&letter))
+}
+
+const ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+const SCALES: [(u64, &str); 6] = [
+    (1_000_000_000_000_000_000, "quintillion"),
+    (1_000_000_000_000_000, "quadrillion"),
+    (1_000_000_000_000, "trillion"),
+    (1_000_000_000, "billion"),
+    (1_000_000, "million"),
+    (1_000, "thousand"),
+];
+
+/// Spell out a number below one thousand in English, without spaces
+fn spell_hundreds(number: u64) -> String {
+    let mut spelled = String::new();
+    if number >= 100 {
+        spelled.push_str(ONES[(number / 100) as usize]);
+        spelled.push_str("hundred");
+    }
+    let rest = number % 100;
+    if rest >= 20 {
+        spelled.push_str(TENS[(rest / 10) as usize]);
+        if !rest.is_multiple_of(10) {
+            spelled.push_str(ONES[(rest % 10) as usize]);
+        }
+    } else if rest > 0 || number == 0 {
+        spelled.push_str(ONES[rest as usize]);
+    }
+    spelled
+}
+
+/// Spell out a run of digits in English, without spaces (e.g. "2024" becomes
+/// "twothousandtwentyfour")
+///
+/// Numbers with leading zeros or too large to fit are spelled digit by digit.
+pub fn spell_number(digits: &str) -> String {
+    match digits.parse::<u64>() {
+        Ok(mut number) if !(digits.starts_with('0') && digits.len() > 1) => {
+            let mut spelled = String::new();
+            for (scale, name) in SCALES {
+                if number >= scale {
+                    spelled.push_str(&spell_hundreds(number / scale));
+                    spelled.push_str(name);
+                    number %= scale;
+                }
+            }
+            if number > 0 || spelled.is_empty() {
+                spelled.push_str(&spell_hundreds(number));
+            }
+            spelled
+        }
+        _ => digits
+            .chars()
+            .filter_map(|digit| digit.to_digit(10))
+            .map(|digit| ONES[digit as usize])
+            .collect(),
+    }
+}
+
+/// Quote a word, escaping only quotes, backslashes and control characters so
+/// that combining marks and emoji sequences are shown as written
+pub fn quote(word: &str) -> String {
+    let mut quoted = String::from('"');
+    for character in word.chars() {
+        if character.is_control() || character == '"' || character == '\\' {
+            quoted.extend(character.escape_debug());
+        } else {
+            quoted.push(character);
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Format a word for the output, with its transliteration if it has one
+pub fn word_label(word: &str, word_value: &WordValue) -> String {
+    match &word_value.transliterated {
+        Some(transliterated) => format!("{} ({transliterated})", quote(word)),
+        None => quote(word),
+    }
+}
+
+/// Format a letter and its value for the per-letter breakdown (e.g. `'a': 1`)
+pub fn letter_line(letter: &LetterValue) -> String {
+    format!("{}: {}", letter_label(&letter.text), letter.value)
+}
+
+/// Format a letter for the per-letter breakdown (e.g. `'a'`)
+pub fn letter_label(letter: &str) -> String {
+    let mut characters = letter.chars();
+    match (characters.next(), characters.next()) {
+        (Some(character), None) => format!("{character:?}"),
+        _ => format!("'{letter}'"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluator(digits: DigitMode) -> Evaluator {
+        Evaluator {
+            normalize: true,
+            transliteration: None,
+            digits,
+            locale: None,
+        }
+    }
+
+    /// The letters of a word with their values, and its total
+    fn breakdown(digits: DigitMode, word: &str) -> (Vec<(String, u8)>, u64) {
+        let word_value = evaluator(digits).evaluate_word(word);
+        let letters = word_value
+            .letters
+            .into_iter()
+            .map(|letter| (letter.text, letter.value))
+            .collect();
+        (letters, word_value.value)
+    }
+
+    fn expected(letters: &[(&str, u8)]) -> Vec<(String, u8)> {
+        letters
+            .iter()
+            .map(|(text, value)| (text.to_string(), *value))
+            .collect()
+    }
+
+    #[test]
+    fn spells_numbers() {
+        for (digits, spelled) in [
+            ("0", "zero"),
+            ("7", "seven"),
+            ("13", "thirteen"),
+            ("19", "nineteen"),
+            ("20", "twenty"),
+            ("42", "fortytwo"),
+            ("100", "onehundred"),
+            ("105", "onehundredfive"),
+            ("110", "onehundredten"),
+            ("2024", "twothousandtwentyfour"),
+            ("1000000", "onemillion"),
+            ("1000001", "onemillionone"),
+            ("3000000000", "threebillion"),
+            // Leading zeros and numbers too large are spelled digit by digit
+            ("007", "zerozeroseven"),
+            ("99999999999999999999", &"nine".repeat(20)),
+        ] {
+            assert_eq!(spell_number(digits), spelled, "{digits}");
+        }
+    }
+
+    #[test]
+    fn digits_score_their_value() {
+        let (letters, total) = breakdown(DigitMode::Value, "2024");
+        assert_eq!(letters, expected(&[("2", 2), ("0", 0), ("2", 2), ("4", 4)]));
+        assert_eq!(total, 8);
+    }
+
+    #[test]
+    fn ignored_digits_are_skipped() {
+        for number in ["0", "13", "105", "2024"] {
+            assert_eq!(
+                breakdown(DigitMode::Ignore, number),
+                (vec![], 0),
+                "{number}"
+            );
+        }
+        assert_eq!(
+            breakdown(DigitMode::Ignore, "a13b"),
+            (expected(&[("a", 1), ("b", 2)]), 3)
+        );
+    }
+
+    #[test]
+    fn whole_numbers_are_reduced_together() {
+        for (number, value) in [("0", 0), ("13", 4), ("105", 6), ("2024", 8), ("99", 9)] {
+            assert_eq!(
+                breakdown(DigitMode::Whole, number),
+                (expected(&[(number, value)]), value as u64),
+                "{number}"
+            );
+        }
+    }
+
+    #[test]
+    fn spelled_numbers_score_their_letters() {
+        assert_eq!(
+            breakdown(DigitMode::Spell, "0"),
+            (expected(&[("z", 7), ("e", 5), ("r", 2), ("o", 7)]), 21)
+        );
+        let (letters, total) = breakdown(DigitMode::Spell, "13");
+        assert_eq!(
+            letters,
+            expected(&[
+                ("t", 4),
+                ("h", 5),
+                ("i", 1),
+                ("r", 2),
+                ("t", 4),
+                ("e", 5),
+                ("e", 5),
+                ("n", 5)
+            ])
+        );
+        assert_eq!(total, 31);
+        // Every spelled letter points at the number it comes from
+        let word_value = evaluator(DigitMode::Spell).evaluate_word("x105");
+        assert!(word_value.letters[1..]
+            .iter()
+            .all(|letter| letter.span == (1..4)));
+        assert_eq!(
+            word_value.value,
+            5 + evaluator(DigitMode::Spell)
+                .evaluate_word("onehundredfive")
+                .value
+        );
+        assert_eq!(
+            breakdown(DigitMode::Spell, "2024").1,
+            breakdown(DigitMode::Spell, "twothousandtwentyfour").1
+        );
+    }
 }
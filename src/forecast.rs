@@ -0,0 +1,357 @@
+use chrono::Datelike;
+use crossterm::style::Stylize;
+use prettytable::{format, Cell, Row, Table};
+use serde_json::{json, Value};
+use std::io::Write;
+
+use crate::args::{ForecastArgs, OutputFormat, CLI};
+use crate::date::DateParser;
+use crate::evaluate::{reduce, Evaluator};
+use crate::markup;
+use crate::style::decorate;
+
+/// Longest forecast, in years
+const MAX_YEARS: u32 = 1000;
+
+/// A letter in transit and the number of years it lasts
+#[derive(Debug, Clone, Copy)]
+pub struct Transit {
+    pub letter: char,
+    pub value: u8,
+}
+
+/// Transits and Essence for a single year of life
+#[derive(Debug, Clone)]
+pub struct ForecastYear {
+    pub year: i32,
+    pub age: u32,
+    pub physical: Option<Transit>,
+    pub mental: Option<Transit>,
+    pub spiritual: Option<Transit>,
+    pub essence: u32,
+}
+
+/// Letters of a name part that carry a value, in order
+fn transit_letters(evaluator: &Evaluator, part: &str) -> Vec<Transit> {
+    evaluator
+        .evaluate_word(part)
+        .letters
+        .iter()
+        .filter_map(|letter| {
+            let first = letter.text.chars().next()?;
+            (first.is_alphabetic() && letter.value > 0).then(|| Transit {
+                letter: first.to_uppercase().next().unwrap_or(first),
+                value: letter.value,
+            })
+        })
+        .collect()
+}
+
+/// Letter in transit at a given age, cycling through the letters from birth
+fn transit_at(letters: &[Transit], age: u32) -> Option<Transit> {
+    let cycle: u32 = letters.iter().map(|transit| transit.value as u32).sum();
+    if cycle == 0 {
+        return None;
+    }
+    let mut position = age % cycle;
+    for transit in letters {
+        if position < transit.value as u32 {
+            return Some(*transit);
+        }
+        position -= transit.value as u32;
+    }
+    None
+}
+
+/// Compute the transits for every year from `from` to `to` (inclusive)
+///
+/// The first name gives the Physical transit, the last name the Spiritual
+/// transit and any middle names (taken together) the Mental transit.
+pub fn forecast(
+    evaluator: &Evaluator,
+    name: &[String],
+    birth_year: i32,
+    from: i32,
+    to: i32,
+) -> Vec<ForecastYear> {
+    let parts: Vec<&str> = name
+        .iter()
+        .flat_map(|part| part.split_whitespace())
+        .collect();
+    let physical = parts
+        .first()
+        .map(|part| transit_letters(evaluator, part))
+        .unwrap_or_default();
+    let spiritual = if parts.len() > 1 {
+        transit_letters(evaluator, parts[parts.len() - 1])
+    } else {
+        vec![]
+    };
+    let mental = if parts.len() > 2 {
+        transit_letters(evaluator, &parts[1..parts.len() - 1].concat())
+    } else {
+        vec![]
+    };
+
+    (from..=to)
+        .map(|year| {
+            // Exact even where `year - birth_year` would overflow an i32
+            let age = year.abs_diff(birth_year);
+            let physical = transit_at(&physical, age);
+            let mental = transit_at(&mental, age);
+            let spiritual = transit_at(&spiritual, age);
+            let essence = [physical, mental, spiritual]
+                .iter()
+                .flatten()
+                .map(|transit| transit.value as u32)
+                .sum();
+            ForecastYear {
+                year,
+                age,
+                physical,
+                mental,
+                spiritual,
+                essence,
+            }
+        })
+        .collect()
+}
+
+/// Format a transit as `L (n)`, or `-` when there is none
+fn transit_label(transit: Option<Transit>) -> String {
+    match transit {
+        Some(transit) => format!("{} ({})", transit.letter, transit.value),
+        None => "-".to_string(),
+    }
+}
+
+/// Format the Essence as `total/reduced`
+fn essence_label(essence: u32) -> String {
+    let reduced = reduce(essence, true);
+    if reduced == essence {
+        essence.to_string()
+    } else {
+        format!("{essence}/{reduced}")
+    }
+}
+
+fn transit_json(transit: Option<Transit>) -> Value {
+    match transit {
+        Some(transit) => json!({ "letter": transit.letter, "value": transit.value }),
+        None => Value::Null,
+    }
+}
+
+/// Run the `forecast` subcommand
+pub fn run(
+    args: &CLI,
+    forecast_args: &ForecastArgs,
+    decorations: bool,
+    output_buffer: &mut impl Write,
+) -> Result<(), String> {
+    let date_parser = DateParser::new(args);
+    let birth_date = date_parser.parse(&forecast_args.birth_date)?;
+    let birth_year = birth_date.year();
+    let from = forecast_args
+        .from
+        .unwrap_or_else(|| chrono::Local::now().year());
+    let to = match forecast_args.to {
+        Some(to) => to,
+        None => from
+            .checked_add(9)
+            .ok_or_else(|| format!("Invalid start year: {from}"))?,
+    };
+    if from < birth_year {
+        return Err(format!(
+            "Forecast cannot start before the birth year ({birth_year})"
+        ));
+    }
+    if to < from {
+        return Err(format!("Invalid year range: {from} to {to}"));
+    }
+    if to.abs_diff(from) >= MAX_YEARS {
+        return Err(format!(
+            "Forecast covers at most {MAX_YEARS} years (asked for {from} to {to})"
+        ));
+    }
+
+    let years = forecast(
+        &Evaluator::new(args)?,
+        &forecast_args.name,
+        birth_year,
+        from,
+        to,
+    );
+
+    let format = args.output_format();
+    let titles = ["Year", "Age", "Physical", "Mental", "Spiritual", "Essence"];
+    let rows: Vec<Vec<String>> = years
+        .iter()
+        .map(|year| {
+            vec![
+                year.year.to_string(),
+                year.age.to_string(),
+                transit_label(year.physical),
+                transit_label(year.mental),
+                transit_label(year.spiritual),
+                essence_label(year.essence),
+            ]
+        })
+        .collect();
+
+    if format == OutputFormat::Markdown {
+        write!(output_buffer, "{}", markup::markdown_table(&titles, &rows)).unwrap();
+    } else if format == OutputFormat::Html {
+        let page = markup::html_table("numeracalc forecast", &titles, &rows, &[], None);
+        write!(output_buffer, "{page}").unwrap();
+    } else if format == OutputFormat::Json {
+        let json_output: Vec<Value> = years
+            .iter()
+            .map(|year| {
+                json!({
+                    "year": year.year,
+                    "age": year.age,
+                    "physical": transit_json(year.physical),
+                    "mental": transit_json(year.mental),
+                    "spiritual": transit_json(year.spiritual),
+                    "essence": year.essence,
+                    "essence_reduced": reduce(year.essence, true),
+                })
+            })
+            .collect();
+        let json_string = serde_json::to_string_pretty(&json_output)
+            .expect("error: Failed to serialize output to json");
+        writeln!(output_buffer, "{}", json_string).unwrap();
+    } else if format != OutputFormat::Text {
+        return Err(format.unsupported("forecast", &OutputFormat::REPORTS));
+    } else if args.less || args.raw {
+        for year in &years {
+            writeln!(
+                output_buffer,
+                "{}: {} {} {} {}",
+                year.year,
+                transit_label(year.physical),
+                transit_label(year.mental),
+                transit_label(year.spiritual),
+                essence_label(year.essence)
+            )
+            .unwrap();
+        }
+    } else {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+        table.set_titles(Row::new(
+            ["YEAR", "AGE", "PHYSICAL", "MENTAL", "SPIRITUAL", "ESSENCE"]
+                .iter()
+                .map(|title| Cell::new(&format!("{}", decorate(title.green(), decorations))))
+                .collect(),
+        ));
+        for year in &years {
+            table.add_row(Row::new(vec![
+                Cell::new(&format!(
+                    "{}",
+                    decorate(year.year.to_string().dark_cyan(), decorations)
+                )),
+                Cell::new(&format!(
+                    "{}",
+                    decorate(year.age.to_string().dark_cyan(), decorations)
+                )),
+                Cell::new(&transit_label(year.physical)),
+                Cell::new(&transit_label(year.mental)),
+                Cell::new(&transit_label(year.spiritual)),
+                Cell::new(&format!(
+                    "{}",
+                    decorate(essence_label(year.essence).blue(), decorations)
+                )),
+            ]));
+        }
+        table
+            .print(output_buffer)
+            .expect("error: Failed to print table");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    fn evaluator() -> Evaluator {
+        Evaluator::new(&CLI::parse_from(["numeracalc"])).unwrap()
+    }
+
+    fn name(name: &str) -> Vec<String> {
+        name.split(' ').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn transits_cycle_through_the_letters() {
+        // J 1, O 7, H 5, N 5: a cycle of 18 years
+        let letters = transit_letters(&evaluator(), "John");
+        let letter_at = |age| transit_at(&letters, age).map(|transit| transit.letter);
+        for (age, letter) in [
+            (0, 'J'),
+            (1, 'O'),
+            (7, 'O'),
+            (8, 'H'),
+            (12, 'H'),
+            (13, 'N'),
+            (17, 'N'),
+            (18, 'J'),
+            (19, 'O'),
+        ] {
+            assert_eq!(letter_at(age), Some(letter), "age {age}");
+        }
+        assert!(transit_at(&[], 5).is_none());
+    }
+
+    #[test]
+    fn essence_sums_the_transits() {
+        let years = forecast(&evaluator(), &name("John Paul Smith"), 1990, 2024, 2026);
+        let summary: Vec<(i32, u32, char, char, char, u32)> = years
+            .iter()
+            .map(|year| {
+                (
+                    year.year,
+                    year.age,
+                    year.physical.unwrap().letter,
+                    year.mental.unwrap().letter,
+                    year.spiritual.unwrap().letter,
+                    year.essence,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (2024, 34, 'N', 'L', 'S', 5 + 3 + 3),
+                (2025, 35, 'N', 'L', 'S', 5 + 3 + 3),
+                (2026, 36, 'J', 'P', 'S', 1 + 8 + 3),
+            ]
+        );
+        // A single name only has a Physical transit
+        let years = forecast(&evaluator(), &name("John"), 1990, 2024, 2024);
+        assert!(years[0].mental.is_none() && years[0].spiritual.is_none());
+        assert_eq!(years[0].essence, 5);
+    }
+
+    #[test]
+    fn rejects_long_forecasts() {
+        let args = CLI::parse_from([
+            "numeracalc",
+            "forecast",
+            "John",
+            "-b",
+            "1990-03-14",
+            "--to",
+            "2147483647",
+        ]);
+        let Some(crate::args::Command::Forecast(forecast_args)) = &args.command else {
+            panic!("not a forecast command");
+        };
+        let error = run(&args, forecast_args, false, &mut vec![]).unwrap_err();
+        assert!(error.starts_with("Forecast covers at most"), "{error}");
+    }
+}
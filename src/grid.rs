@@ -0,0 +1,81 @@
+use crossterm::style::Stylize;
+use prettytable::{format, Cell, Row, Table};
+use std::io::Write;
+
+use crate::evaluate::WordValue;
+use crate::style::decorate;
+
+/// An entry of the `--layout grid` table
+pub enum GridEntry {
+    /// A word (as labelled in the output) and its letters
+    Word(String, WordValue),
+    /// A file total or the overall total
+    Total(String, u64),
+}
+
+/// Render the words as a table with one letter per column, the letter values
+/// underneath and the totals in the footer
+pub fn render(output_buffer: &mut impl Write, entries: &[GridEntry], decorations: bool) {
+    let columns = entries
+        .iter()
+        .map(|entry| match entry {
+            GridEntry::Word(_, word_value) => word_value.letters.len(),
+            GridEntry::Total(..) => 0,
+        })
+        .max()
+        .unwrap_or(0);
+    let title = |text: &str| Cell::new(&decorate(text.green(), decorations).to_string());
+    let empty = || Cell::new("");
+
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+    let mut titles = vec![title("WORD")];
+    titles.extend((1..=columns).map(|column| title(&column.to_string())));
+    titles.push(title("VALUE"));
+    table.set_titles(Row::new(titles));
+
+    let mut after_word = false;
+    for entry in entries {
+        match entry {
+            GridEntry::Word(label, word_value) => {
+                let mut letters = vec![Cell::new(
+                    &decorate(label.clone().blue(), decorations).to_string(),
+                )];
+                let mut values = vec![empty()];
+                for letter in &word_value.letters {
+                    letters.push(Cell::new(
+                        &decorate(letter.text.clone().dark_cyan(), decorations).to_string(),
+                    ));
+                    values.push(Cell::new(&letter.value.to_string().blue().to_string()));
+                }
+                letters.resize_with(columns + 1, empty);
+                values.resize_with(columns + 1, empty);
+                letters.push(empty());
+                values.push(Cell::new(
+                    &decorate(word_value.value.to_string().blue(), decorations).to_string(),
+                ));
+                table.add_row(Row::new(letters));
+                table.add_row(Row::new(values));
+                after_word = true;
+            }
+            GridEntry::Total(label, total) => {
+                // Separate the totals from the words above them
+                if after_word {
+                    table.add_empty_row();
+                    after_word = false;
+                }
+                let mut row = vec![Cell::new(
+                    &decorate(label.clone().blue(), decorations).to_string(),
+                )];
+                row.resize_with(columns + 1, empty);
+                row.push(Cell::new(
+                    &decorate(total.to_string().blue(), decorations).to_string(),
+                ));
+                table.add_row(Row::new(row));
+            }
+        }
+    }
+    table
+        .print(output_buffer)
+        .expect("error: Failed to print table");
+}
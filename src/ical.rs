@@ -0,0 +1,287 @@
+use chrono::{Datelike, Days, NaiveDate, Utc};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+};
+
+use crate::args::{CalendarArgs, OutputFormat, CLI};
+use crate::cycles::{meaning, personal_day, personal_month, personal_year};
+use crate::date::DateParser;
+
+/// Escape a TEXT value (RFC 5545, section 3.3.11)
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+/// Write a content line, folding it at 75 octets (RFC 5545, section 3.1)
+fn write_line(output: &mut impl Write, line: &str) -> io::Result<()> {
+    let mut width = 0;
+    for character in line.chars() {
+        let length = character.len_utf8();
+        if width + length > 75 {
+            output.write_all(b"\r\n ")?;
+            width = 1;
+        }
+        write!(output, "{character}")?;
+        width += length;
+    }
+    output.write_all(b"\r\n")
+}
+
+/// Format a date as an iCalendar DATE value
+fn ical_date(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+/// Describe a cycle number as `N (Meaning)`
+fn cycle_label(number: u32) -> String {
+    format!("{number} ({})", meaning(number))
+}
+
+/// Write a single all-day event
+fn write_event(
+    output: &mut impl Write,
+    uid: &str,
+    stamp: &str,
+    date: NaiveDate,
+    summary: &str,
+) -> io::Result<()> {
+    let next_day = date.checked_add_days(Days::new(1)).unwrap_or(date);
+    write_line(output, "BEGIN:VEVENT")?;
+    write_line(output, &format!("UID:{uid}"))?;
+    write_line(output, &format!("DTSTAMP:{stamp}"))?;
+    write_line(output, &format!("DTSTART;VALUE=DATE:{}", ical_date(date)))?;
+    write_line(output, &format!("DTEND;VALUE=DATE:{}", ical_date(next_day)))?;
+    write_line(output, &format!("SUMMARY:{}", escape_text(summary)))?;
+    write_line(output, "TRANSP:TRANSPARENT")?;
+    write_line(output, "END:VEVENT")
+}
+
+/// Write the calendar covering `from` to `to` (inclusive)
+pub fn write_calendar(
+    output: &mut impl Write,
+    birth_date: NaiveDate,
+    from: NaiveDate,
+    to: NaiveDate,
+    transitions: bool,
+) -> io::Result<()> {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let birth = ical_date(birth_date);
+
+    write_line(output, "BEGIN:VCALENDAR")?;
+    write_line(output, "VERSION:2.0")?;
+    write_line(
+        output,
+        &format!(
+            "PRODID:-//numeracalc//numeracalc {}//EN",
+            env!("CARGO_PKG_VERSION")
+        ),
+    )?;
+    write_line(output, "CALSCALE:GREGORIAN")?;
+    write_line(output, "METHOD:PUBLISH")?;
+    write_line(
+        output,
+        &format!(
+            "X-WR-CALNAME:Personal Days ({})",
+            birth_date.format("%Y-%m-%d")
+        ),
+    )?;
+
+    for date in from.iter_days().take_while(|date| *date <= to) {
+        let day = ical_date(date);
+        if transitions && (date == from || (date.month() == 1 && date.day() == 1)) {
+            write_event(
+                output,
+                &format!("{day}-personal-year-{birth}@numeracalc"),
+                &stamp,
+                date,
+                &format!(
+                    "Personal Year {}",
+                    cycle_label(personal_year(birth_date, date.year()))
+                ),
+            )?;
+        }
+        if transitions && (date == from || date.day() == 1) {
+            write_event(
+                output,
+                &format!("{day}-personal-month-{birth}@numeracalc"),
+                &stamp,
+                date,
+                &format!(
+                    "Personal Month {}",
+                    cycle_label(personal_month(birth_date, date))
+                ),
+            )?;
+        }
+        write_event(
+            output,
+            &format!("{day}-personal-day-{birth}@numeracalc"),
+            &stamp,
+            date,
+            &format!(
+                "Personal Day {}",
+                cycle_label(personal_day(birth_date, date))
+            ),
+        )?;
+    }
+
+    write_line(output, "END:VCALENDAR")
+}
+
+/// Run the `calendar` subcommand
+pub fn run(
+    args: &CLI,
+    calendar_args: &CalendarArgs,
+    output_buffer: &mut impl Write,
+) -> Result<(), String> {
+    // The calendar is always written as iCalendar text
+    let format = args.output_format();
+    if format != OutputFormat::Text {
+        return Err(format.unsupported("calendar", &[OutputFormat::Text]));
+    }
+
+    let date_parser = DateParser::new(args);
+    let birth_date = date_parser.parse(&calendar_args.birth_date)?;
+    let from = date_parser.parse(&calendar_args.from)?;
+    let to = date_parser.parse(&calendar_args.to)?;
+    if to < from {
+        return Err(format!(
+            "Invalid date range: {} to {}",
+            calendar_args.from, calendar_args.to
+        ));
+    }
+
+    match calendar_args.output.as_deref() {
+        None | Some("-") => write_calendar(
+            output_buffer,
+            birth_date,
+            from,
+            to,
+            calendar_args.transitions,
+        )
+        .map_err(|e| format!("Failed to write calendar: {e}")),
+        Some(path) => {
+            let file = File::create(path).map_err(|e| format!("Failed to create {path:?}: {e}"))?;
+            let mut file_buffer = BufWriter::new(file);
+            write_calendar(
+                &mut file_buffer,
+                birth_date,
+                from,
+                to,
+                calendar_args.transitions,
+            )
+            .and_then(|_| file_buffer.flush())
+            .map_err(|e| format!("Failed to write {path:?}: {e}"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::args::Command;
+    use clap::Parser;
+
+    fn folded(line: &str) -> String {
+        let mut output = Vec::new();
+        write_line(&mut output, line).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn escapes_text() {
+        assert_eq!(
+            escape_text("One, two; three\nfour\\five"),
+            r"One\, two\; three\nfour\\five"
+        );
+    }
+
+    #[test]
+    fn folds_long_lines_at_75_octets() {
+        assert_eq!(folded(&"a".repeat(75)), format!("{}\r\n", "a".repeat(75)));
+        assert_eq!(
+            folded(&"a".repeat(160)),
+            format!(
+                "{}\r\n {}\r\n {}\r\n",
+                "a".repeat(75),
+                "a".repeat(74),
+                "a".repeat(11)
+            )
+        );
+    }
+
+    #[test]
+    fn folds_before_multibyte_characters() {
+        // "é" is two octets and would straddle the 75th octet
+        let line = format!("{}é{}", "a".repeat(74), "b".repeat(3));
+        assert_eq!(folded(&line), format!("{}\r\n ébbb\r\n", "a".repeat(74)));
+
+        let line = "€".repeat(30);
+        let output = folded(&line);
+        for physical in output.split("\r\n").filter(|line| !line.is_empty()) {
+            assert!(
+                physical.len() <= 75,
+                "{physical:?} is longer than 75 octets"
+            );
+        }
+        assert_eq!(output.replace("\r\n ", ""), format!("{line}\r\n"));
+    }
+
+    #[test]
+    fn calendar_lines_are_valid() {
+        let date = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        let mut output = Vec::new();
+        write_calendar(
+            &mut output,
+            date(1990, 3, 14),
+            date(2024, 12, 31),
+            date(2025, 1, 1),
+            true,
+        )
+        .unwrap();
+        let calendar = String::from_utf8(output).unwrap();
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+        assert!(calendar.split("\r\n").all(|line| line.len() <= 75));
+        assert_eq!(calendar.matches("BEGIN:VEVENT").count(), 6);
+        assert!(calendar.contains("SUMMARY:Personal Year 8 ("));
+    }
+
+    /// Run the calendar for a couple of days with the given options
+    fn calendar(options: &[&str]) -> Result<String, String> {
+        let args = CLI::parse_from(
+            ["numeracalc", "calendar", "-b", "1990-03-14"]
+                .into_iter()
+                .chain(["--from", "2024-06-18", "--to", "2024-06-19"])
+                .chain(options.iter().copied()),
+        );
+        let Some(Command::Calendar(calendar_args)) = &args.command else {
+            panic!("not a calendar command");
+        };
+        let mut output = Vec::new();
+        run(&args, calendar_args, &mut output)?;
+        Ok(String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn rejects_formats_other_than_text() {
+        assert!(calendar(&[]).unwrap().starts_with("BEGIN:VCALENDAR\r\n"));
+        for options in [&["--format", "csv"][..], &["--format", "json"], &["--json"]] {
+            let error = calendar(options).unwrap_err();
+            assert!(
+                error.starts_with("The calendar command does not support --format"),
+                "{error}"
+            );
+            assert!(error.ends_with("(use text)"), "{error}");
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+};
+
+use atty::Stream;
+
+use crate::args::{OutputFormat, CLI};
+use crate::output::Report;
+use crate::tokenize::Tokenizer;
+
+/// Expand the `--input` paths and glob patterns into the files they match
+pub fn expand_inputs(patterns: &[String]) -> Result<Vec<String>, String> {
+    let mut files = vec![];
+    for pattern in patterns {
+        let paths = glob::glob(pattern).map_err(|e| format!("Invalid pattern {pattern:?}: {e}"))?;
+        let mut matched = false;
+        for path in paths {
+            let path = path.map_err(|e| format!("Failed to read {:?}: {}", e.path(), e.error()))?;
+            if path.is_file() {
+                files.push(path.to_string_lossy().into_owned());
+                matched = true;
+            }
+        }
+        if !matched {
+            return Err(format!("No such file: {pattern:?}"));
+        }
+    }
+    Ok(files)
+}
+
+/// Evaluate the words of a reader as it is read, writing each line's values
+/// as soon as it ends
+///
+/// With whitespace or `--delimiter` separators the words are split off as the
+/// input arrives, so only the current word is held in memory. A `--split-regex`
+/// and `--null-data` work on whole lines (NUL-separated records with `--null-data`).
+pub fn stream_words<W: Write>(
+    reader: impl BufRead,
+    name: &str,
+    tokenizer: &Tokenizer,
+    report: &mut Report<W>,
+) -> Result<(), String> {
+    if tokenizer.splits_by_character() {
+        stream_split_words(reader, name, tokenizer, report)
+    } else {
+        stream_lines(reader, name, tokenizer, report)
+    }
+}
+
+/// Split words off the input one character at a time
+fn stream_split_words<W: Write>(
+    mut reader: impl BufRead,
+    name: &str,
+    tokenizer: &Tokenizer,
+    report: &mut Report<W>,
+) -> Result<(), String> {
+    let terminator = if tokenizer.null_data { '\0' } else { '\n' };
+    // Bytes read but not yet decoded, as a character may span two reads
+    let mut pending: Vec<u8> = vec![];
+    let mut word = String::new();
+    loop {
+        let length = {
+            let chunk = reader
+                .fill_buf()
+                .map_err(|e| format!("Failed to read {name}: {e}"))?;
+            pending.extend_from_slice(chunk);
+            chunk.len()
+        };
+        if length == 0 {
+            break;
+        }
+        reader.consume(length);
+        let valid = match std::str::from_utf8(&pending) {
+            Ok(text) => text.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => return Err(format!("Failed to read {name}: {e}")),
+        };
+        for character in std::str::from_utf8(&pending[..valid]).unwrap().chars() {
+            if character == terminator || tokenizer.is_separator(character) {
+                for word in tokenizer.split(&word) {
+                    report.word(&word)?;
+                }
+                word.clear();
+                if character == terminator {
+                    report.flush();
+                }
+            } else {
+                word.push(character);
+            }
+        }
+        pending.drain(..valid);
+    }
+    if let Err(e) = std::str::from_utf8(&pending) {
+        return Err(format!("Failed to read {name}: {e}"));
+    }
+    for word in tokenizer.split(&word) {
+        report.word(&word)?;
+    }
+    report.flush();
+    Ok(())
+}
+
+/// Evaluate the words of a reader line by line (or NUL-separated record by
+/// record with `--null-data`)
+fn stream_lines<W: Write>(
+    mut reader: impl BufRead,
+    name: &str,
+    tokenizer: &Tokenizer,
+    report: &mut Report<W>,
+) -> Result<(), String> {
+    let terminator = if tokenizer.null_data { b'\0' } else { b'\n' };
+    let mut line = vec![];
+    loop {
+        line.clear();
+        let length = reader
+            .read_until(terminator, &mut line)
+            .map_err(|e| format!("Failed to read {name}: {e}"))?;
+        if length == 0 {
+            return Ok(());
+        }
+        if line.last() == Some(&terminator) {
+            line.pop();
+        }
+        let text = std::str::from_utf8(&line)
+            .map_err(|e| format!("Failed to read {name}: {e}"))?
+            .trim_end_matches('\r');
+        for word in tokenizer.split(text) {
+            report.word(&word)?;
+        }
+        report.flush();
+    }
+}
+
+/// Evaluate the words of every `--input` file, with a total per file
+fn stream_inputs<W: Write>(
+    patterns: &[String],
+    tokenizer: &Tokenizer,
+    report: &mut Report<W>,
+) -> Result<(), String> {
+    for file in expand_inputs(patterns)? {
+        let reader = File::open(&file).map_err(|e| format!("Failed to open {file:?}: {e}"))?;
+        report.begin_file(&file);
+        stream_words(
+            BufReader::new(reader),
+            &format!("{file:?}"),
+            tokenizer,
+            report,
+        )?;
+        report.end_file();
+    }
+    Ok(())
+}
+
+/// Whether stdin is read after the words without being asked for with `-`
+///
+/// `--stdin` and `--no-stdin` override the detection, which otherwise reads stdin
+/// when it is not a terminal and no `--input` files were given, or line by line
+/// in `--recursive` mode with `--format ndjson`.
+fn implicit_stdin(args: &CLI) -> bool {
+    if args.stdin {
+        true
+    } else if args.no_stdin || args.words.iter().any(|word| word == "-") {
+        false
+    } else {
+        (args.input.is_empty() && !atty::is(Stream::Stdin))
+            || (args.recursive && args.output_format() == OutputFormat::Ndjson)
+    }
+}
+
+/// Whether the words come from stdin or files rather than only from the arguments
+pub fn has_sources(args: &CLI) -> bool {
+    !args.input.is_empty() || args.words.iter().any(|word| word == "-") || implicit_stdin(args)
+}
+
+/// Evaluate the words of every source in order: the arguments (reading stdin
+/// in place of `-`), then stdin if it is read implicitly, then the `--input` files
+pub fn stream_sources<W: Write>(
+    args: &CLI,
+    tokenizer: &Tokenizer,
+    report: &mut Report<W>,
+) -> Result<(), String> {
+    if args.no_stdin && args.words.iter().any(|word| word == "-") {
+        return Err("Cannot read \"-\" from stdin with --no-stdin".to_string());
+    }
+    // The arguments are all known up front, so check them before writing any
+    let positional: Vec<String> = args
+        .words
+        .iter()
+        .filter(|word| *word != "-")
+        .flat_map(|word| tokenizer.split(word))
+        .collect();
+    report.check_words(&positional)?;
+    let mut stdin_read = false;
+    for word in &args.words {
+        if word == "-" {
+            if !stdin_read {
+                stream_words(io::stdin().lock(), "<stdin>", tokenizer, report)?;
+                stdin_read = true;
+            }
+        } else {
+            for word in tokenizer.split(word) {
+                report.write_word(&word)?;
+            }
+        }
+    }
+    report.flush();
+    if implicit_stdin(args) {
+        stream_words(io::stdin().lock(), "<stdin>", tokenizer, report)?;
+    }
+    stream_inputs(&args.input, tokenizer, report)
+}
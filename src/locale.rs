@@ -0,0 +1,165 @@
+use serde_json::Value;
+use std::fs;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::args::{LocaleName, CLI};
+
+/// How a locale-specific letter is valued
+#[derive(Debug, Clone)]
+pub enum LetterRule {
+    /// Valued as the sum of these letters (e.g. ß as "ss")
+    Spelling(String),
+    /// A fixed value
+    Value(u8),
+}
+
+/// Letters of a locale that are not a single character of the table
+#[derive(Debug, Clone, Default)]
+pub struct Locale {
+    /// Letters (in lowercase) and their rules, longest first
+    letters: Vec<(String, LetterRule)>,
+    /// Use Turkish case rules (I lowercases to ı, İ to i)
+    turkish: bool,
+}
+
+/// Built-in letters for each locale
+fn builtin_letters(locale: &LocaleName) -> &'static [(&'static str, &'static str)] {
+    match locale {
+        LocaleName::De => &[
+            ("ß", "ss"),
+            ("ẞ", "ss"),
+            ("ä", "ae"),
+            ("ö", "oe"),
+            ("ü", "ue"),
+        ],
+        LocaleName::Nl => &[("ĳ", "ij"), ("ij", "ij")],
+        LocaleName::Es => &[("ch", "ch"), ("ll", "ll"), ("ñ", "n")],
+        LocaleName::Da | LocaleName::No => &[("æ", "ae"), ("ø", "oe"), ("å", "aa")],
+        LocaleName::Tr => &[("ı", "i"), ("i̇", "i")],
+    }
+}
+
+impl Locale {
+    /// Create the locale from `--locale` and `--locale-file`, if either was given
+    pub fn new(args: &CLI) -> Result<Option<Locale>, String> {
+        if args.locale.is_none() && args.locale_file.is_none() {
+            return Ok(None);
+        }
+        let mut locale = Locale {
+            turkish: matches!(args.locale, Some(LocaleName::Tr)),
+            ..Locale::default()
+        };
+        if let Some(name) = &args.locale {
+            for (letter, spelling) in builtin_letters(name) {
+                locale.insert(letter, LetterRule::Spelling(spelling.to_string()));
+            }
+        }
+        if let Some(path) = &args.locale_file {
+            locale.load(path)?;
+        }
+        Ok(Some(locale))
+    }
+
+    /// Add or replace a letter
+    fn insert(&mut self, letter: &str, rule: LetterRule) {
+        let letter: String = self.lowercase(letter).nfc().collect();
+        self.letters.retain(|(existing, _)| *existing != letter);
+        self.letters.push((letter, rule));
+        self.letters
+            .sort_by_key(|(letter, _)| std::cmp::Reverse(letter.chars().count()));
+    }
+
+    /// Load letters from a JSON file mapping each letter to a spelling or a value,
+    /// e.g. `{"ch": 8, "ß": "ss"}`
+    fn load(&mut self, path: &str) -> Result<(), String> {
+        let contents =
+            fs::read_to_string(path).map_err(|e| format!("Failed to read {path:?}: {e}"))?;
+        let json: Value = serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse {path:?}: {e}"))?;
+        let Value::Object(letters) = json else {
+            return Err(format!(
+                "Failed to parse {path:?}: expected an object of letters"
+            ));
+        };
+        for (letter, rule) in letters {
+            let rule = match rule {
+                Value::String(spelling) => LetterRule::Spelling(spelling),
+                Value::Number(value) => {
+                    match value.as_u64().and_then(|value| u8::try_from(value).ok()) {
+                        Some(value) => LetterRule::Value(value),
+                        None => {
+                            return Err(format!("Invalid value {value} for {letter:?} in {path:?}"))
+                        }
+                    }
+                }
+                _ => {
+                    return Err(format!(
+                        "Invalid rule for {letter:?} in {path:?} (expected a spelling or a value)"
+                    ))
+                }
+            };
+            self.insert(&letter, rule);
+        }
+        Ok(())
+    }
+
+    /// Lowercase text, following Turkish rules if enabled
+    fn lowercase(&self, text: &str) -> String {
+        if self.turkish {
+            text.chars()
+                .map(|character| match character {
+                    'I' => "ı".to_string(),
+                    'İ' => "i̇".to_string(),
+                    _ => character.to_lowercase().to_string(),
+                })
+                .collect()
+        } else {
+            text.to_lowercase()
+        }
+    }
+
+    /// Find a locale letter at the start of `text`, returning its length in bytes
+    ///
+    /// The text is compared letter by letter in NFC, so that decomposed input
+    /// (like u followed by a combining diaeresis) matches precomposed letters.
+    pub fn match_letter(&self, text: &str) -> Option<(usize, &LetterRule)> {
+        self.letters.iter().find_map(|(letter, rule)| {
+            let mut lowered = String::new();
+            for (index, grapheme) in text.grapheme_indices(true) {
+                lowered.push_str(&self.lowercase(grapheme));
+                let composed: String = lowered.nfc().collect();
+                if composed == *letter {
+                    return Some((index + grapheme.len(), rule));
+                }
+                if !letter.starts_with(&composed) {
+                    break;
+                }
+            }
+            None
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use crate::args::CLI;
+    use crate::evaluate::Evaluator;
+
+    #[test]
+    fn decomposed_letters_match_the_locale() {
+        let args = CLI::parse_from(["numeracalc", "--locale", "de"]);
+        let evaluator = Evaluator::new(&args).unwrap();
+        let composed = "M\u{fc}ller";
+        let decomposed = "Mu\u{308}ller";
+        let value = evaluator.evaluate_word(composed).value;
+        // ü is spelled "ue": M 4 + u 6 + e 5 + l 3 + l 3 + e 5 + r 2
+        assert_eq!(value, 28);
+        assert_eq!(evaluator.evaluate_word(decomposed).value, value);
+        let letters = evaluator.evaluate_word(decomposed).letters;
+        assert_eq!(letters[1].text, "u\u{308}");
+        assert_eq!(letters[1].span, 1..4);
+    }
+}
@@ -6,20 +6,40 @@ use crossterm::{
     terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use prettytable::{format, Cell, Row, Table};
-use serde_json::{json, Value};
+use serde_json::Value;
 use std::{
-    collections::HashMap,
-    io::{self, stdin, BufRead, BufReader, BufWriter, Write},
+    io::{self, stdin, BufWriter, Write},
     process, thread,
 };
 
 mod args;
+mod chart;
+mod csv_input;
+mod cycles;
 mod data;
+mod date;
+mod dates;
 mod evaluate;
-
-use args::{Color, Decorations, CLI};
+mod forecast;
+mod grid;
+mod ical;
+mod input;
+mod locale;
+mod markup;
+mod output;
+mod sqlite;
+mod style;
+mod template;
+mod tokenize;
+mod transliterate;
+mod xlsx;
+
+use args::{Color, Command, DatesCommand, Decorations, OutputFormat, WordLayout, CLI};
 use data::VALUE_TABLE;
-use evaluate::evaluate;
+use evaluate::{letter_line, word_label, Evaluator};
+use output::{report_unknown_chars, Report};
+use template::Templates;
+use tokenize::Tokenizer;
 
 /// Main Function
 fn main() {
@@ -46,21 +66,76 @@ fn main() {
 
     let decorations = decorations;
 
+    if let Some(command) = &args.command {
+        let result = match command {
+            Command::Forecast(forecast_args) => {
+                forecast::run(&args, forecast_args, decorations, &mut output_buffer)
+            }
+            Command::Calendar(calendar_args) => ical::run(&args, calendar_args, &mut output_buffer),
+            Command::Chart(chart_args) => {
+                chart::run(&args, chart_args, decorations, &mut output_buffer)
+            }
+            Command::Dates(DatesCommand::Find(find_args)) => {
+                dates::run_find(&args, find_args, decorations, &mut output_buffer)
+            }
+        };
+        if let Err(e) = result {
+            output_buffer.flush().unwrap();
+            eprintln!("error: {e}");
+            process::exit(1);
+        }
+        output_buffer.flush().unwrap();
+        process::exit(0);
+    }
+
+    if args.json_schema {
+        let json_string = serde_json::to_string_pretty(&output::json_schema())
+            .expect("error: Failed to serialize output to json");
+        writeln!(output_buffer, "{}", json_string).unwrap();
+        output_buffer.flush().unwrap();
+        process::exit(0);
+    }
+
     if args.table {
-        if args.json {
+        let format = args.output_format();
+        if matches!(
+            format,
+            OutputFormat::Json | OutputFormat::Yaml | OutputFormat::Toml
+        ) {
             // Convert phf::Map into a sequence of key-value pairs to preserve order
             let json_object: Vec<(char, u8)> =
                 VALUE_TABLE.entries().map(|(k, v)| (*k, *v)).collect();
 
-            // Serialize the sequence into JSON
+            // Serialize the sequence into JSON, YAML or TOML
             let json_value: Value = json_object.into_iter().collect();
-            let json_string = serde_json::to_string_pretty(&json_value).expect(
-                json!("error: Failed to serialize output to json")
-                    .as_str()
-                    .expect("error: Failed to serialize output to json"),
-            );
+            let table = output::serialize(&json_value, format)
+                .expect("error: Failed to serialize the table");
 
-            writeln!(output_buffer, "{}", json_string).unwrap();
+            write!(output_buffer, "{}", table).unwrap();
+        } else if matches!(format, OutputFormat::Csv | OutputFormat::Tsv) {
+            let delimiter = if format == OutputFormat::Tsv {
+                b'\t'
+            } else {
+                b','
+            };
+            let mut writer = csv::WriterBuilder::new()
+                .delimiter(delimiter)
+                .from_writer(&mut output_buffer);
+            writer.write_record(["character", "value"]).unwrap();
+            for (character, value) in &VALUE_TABLE {
+                writer
+                    .write_record([character.to_string(), value.to_string()])
+                    .unwrap();
+            }
+            writer.flush().unwrap();
+        } else if format == OutputFormat::Ndjson {
+            for (character, value) in &VALUE_TABLE {
+                let line = serde_json::json!({ "character": character, "value": value });
+                writeln!(output_buffer, "{line}").unwrap();
+            }
+        } else if matches!(format, OutputFormat::Markdown | OutputFormat::Html) {
+            let note = "Note: Both lowercase and uppercase letters hold equivalent value.";
+            markup::write_table(&mut output_buffer, format, (!args.quiet).then_some(note));
         } else if args.less || args.raw {
             for (character, value) in &VALUE_TABLE {
                 writeln!(output_buffer, "{character}: {value}").unwrap();
@@ -164,122 +239,65 @@ fn main() {
         process::exit(0);
     }
 
-    let mut values: Vec<u32> = vec![];
-
-    if !atty::is(Stream::Stdin) {
-        let words;
-        match read_file_to_vector() {
-            Ok(val) => {
-                words = val;
-            }
-            Err(e) => {
-                eprintln!("error: {e}");
-                process::exit(1);
-            }
+    let evaluator = match Evaluator::new(&args) {
+        Ok(evaluator) => evaluator,
+        Err(e) => {
+            eprintln!("error: {e}");
+            process::exit(1);
         }
-        if args.json {
-            let mut json_output: Vec<Value> = vec![];
-            let mut total = 0;
-
-            for word in &words {
-                let mut word_json = HashMap::new();
-                let mut value: u32 = 0;
-
-                for letter in word.chars() {
-                    let val = evaluate(letter);
-                    value += val as u32;
-                }
-                total += value;
-                word_json.insert(word, value);
-                json_output.push(json!(word_json));
-            }
-            if !args.no_total {
-                let mut word_json = HashMap::new();
-                word_json.insert("TOTAL_VALUE", total);
-                json_output.push(json!(word_json));
-            }
-
-            let json_string = serde_json::to_string_pretty(&json_output).unwrap();
-            writeln!(output_buffer, "{}", json_string).unwrap();
-        } else {
-            for word in &words {
-                let mut value: u32 = 0;
-                let mut letter_values: Vec<u8> = vec![];
-                for letter in word.chars() {
-                    let val = evaluate(letter);
-                    letter_values.push(val);
-                    value += val as u32;
-                }
-                values.push(value);
-                if !args.raw {
-                    if decorations {
-                        writeln!(
-                            output_buffer,
-                            "{}",
-                            format!("Value of {word:?}: {value}").blue().bold()
-                        )
-                        .unwrap();
-                    } else {
-                        writeln!(
-                            output_buffer,
-                            "{}",
-                            format!("Value of {word:?}: {value}").blue()
-                        )
-                        .unwrap();
-                    }
-                } else {
-                    if decorations {
-                        writeln!(
-                            output_buffer,
-                            "{}",
-                            format!("{word:?}: {value}").blue().bold()
-                        )
-                        .unwrap();
-                    } else {
-                        writeln!(output_buffer, "{}", format!("{word:?}: {value}").blue()).unwrap();
-                    }
-                }
-                if !args.less && !args.raw {
-                    for (idx, letter) in word.chars().enumerate() {
-                        writeln!(
-                            output_buffer,
-                            "{}",
-                            format!("{letter:?}: {}", letter_values[idx]).dark_cyan()
-                        )
-                        .unwrap();
-                    }
-                }
-            }
-            if !args.raw {
-                if decorations {
-                    writeln!(output_buffer, "{}", "-".repeat(20).blue().bold()).unwrap();
-                } else {
-                    writeln!(output_buffer, "{}", "-".repeat(20).blue()).unwrap();
-                }
-            }
-            if !args.no_total {
-                let mut total = 0;
-                for value in &values {
-                    total += value;
-                }
-                if decorations {
-                    writeln!(
-                        output_buffer,
-                        "{}",
-                        format!("Total Value: {total}").blue().bold()
-                    )
-                    .unwrap();
-                } else {
-                    writeln!(output_buffer, "{}", format!("Total Value: {total}").blue()).unwrap();
-                }
-            }
+    };
+    let tokenizer = match Tokenizer::new(&args) {
+        Ok(tokenizer) => tokenizer,
+        Err(e) => {
+            eprintln!("error: {e}");
+            process::exit(1);
+        }
+    };
+    let templates = match Templates::new(&args) {
+        Ok(templates) => templates,
+        Err(e) => {
+            eprintln!("error: {e}");
+            process::exit(1);
+        }
+    };
+    let mut values: Vec<u64> = vec![];
+
+    if args.csv {
+        if let Err(e) = csv_input::run(&args, &evaluator, &tokenizer, &mut output_buffer) {
+            output_buffer.flush().unwrap();
+            eprintln!("error: {e}");
+            process::exit(1);
         }
         output_buffer.flush().unwrap();
+        process::exit(0);
+    }
 
+    if input::has_sources(&args) {
+        let mut report = Report::new(
+            &mut output_buffer,
+            &args,
+            decorations,
+            &evaluator,
+            &templates,
+        )
+        .unwrap_or_else(|e| {
+            eprintln!("error: {e}");
+            process::exit(1);
+        });
+        let result =
+            input::stream_sources(&args, &tokenizer, &mut report).and_then(|_| report.finish());
+        if let Err(e) = result {
+            report.close();
+            eprintln!("error: {e}");
+            process::exit(1);
+        }
         process::exit(0);
     }
 
-    if args.recursive {
+    // Prompting only makes sense on a terminal; otherwise score the words once
+    let interactive = !args.no_stdin && atty::is(Stream::Stdin);
+
+    if args.recursive && interactive {
         if !args.quiet {
             if decorations {
                 writeln!(
@@ -297,32 +315,38 @@ fn main() {
                 .unwrap();
             }
         }
-        let mut words = args.words.clone();
-        if args.json {
+        let mut words = tokenizer.split_all(&args.words);
+        if args.output_format() != OutputFormat::Text
+            || templates.is_active()
+            || args.output.is_some()
+            || args.sqlite.is_some()
+            || matches!(args.layout, WordLayout::Grid)
+        {
             loop {
-                let mut json_output: Vec<Value> = vec![];
-                let mut total = 0;
-
-                for word in &words {
-                    let mut word_json = HashMap::new();
-                    let mut value: u32 = 0;
-
-                    for letter in word.chars() {
-                        let val = evaluate(letter);
-                        value += val as u32;
+                let mut report = Report::new(
+                    &mut output_buffer,
+                    &args,
+                    decorations,
+                    &evaluator,
+                    &templates,
+                )
+                .unwrap_or_else(|e| {
+                    eprintln!("error: {e}");
+                    process::exit(1);
+                });
+                match report.check_words(&words) {
+                    Ok(()) => {
+                        for word in &words {
+                            if let Err(e) = report.write_word(word) {
+                                eprintln!("error: {e}");
+                            }
+                        }
                     }
-                    total += value;
-                    word_json.insert(word, value);
-                    json_output.push(json!(word_json));
+                    Err(e) => eprintln!("error: {e}"),
                 }
-                if !args.no_total {
-                    let mut word_json = HashMap::new();
-                    word_json.insert("TOTAL_VALUE", total);
-                    json_output.push(json!(word_json));
+                if let Err(e) = report.finish() {
+                    eprintln!("error: {e}");
                 }
-
-                let json_string = serde_json::to_string_pretty(&json_output).unwrap();
-                writeln!(output_buffer, "{}", json_string).unwrap();
                 words.clear();
 
                 if !args.raw && !args.less {
@@ -335,40 +359,45 @@ fn main() {
 
                 let mut input = String::new();
                 output_buffer.flush().unwrap();
-                stdin()
+                let read = stdin()
                     .read_line(&mut input)
                     .expect("error: Failed to read line");
+                // Stop at the end of input instead of scoring empty lines forever
+                if read == 0 {
+                    writeln!(output_buffer).unwrap();
+                    output_buffer.flush().unwrap();
+                    process::exit(0);
+                }
 
                 // Trim the trailing newline character
                 input = input.trim().to_string();
 
                 // Split the input string into multiple strings
-                words = input.split_whitespace().map(|s| s.to_string()).collect();
+                words = tokenizer.split(&input);
             }
         } else {
             loop {
+                if report_unknown_chars(&args, &evaluator, &words).is_none() {
+                    words.clear();
+                }
                 for word in &words {
-                    let mut value: u32 = 0;
-                    let mut letter_values: Vec<u8> = vec![];
-                    for letter in word.chars() {
-                        let val = evaluate(letter);
-                        letter_values.push(val);
-                        value += val as u32;
-                    }
+                    let word_value = evaluator.evaluate_word(word);
+                    let value = word_value.value;
+                    let word = word_label(word, &word_value);
                     values.push(value);
                     if !args.raw {
                         if decorations {
                             writeln!(
                                 output_buffer,
                                 "{}",
-                                format!("Value of {word:?}: {value}").blue().bold()
+                                format!("Value of {word}: {value}").blue().bold()
                             )
                             .unwrap();
                         } else {
                             writeln!(
                                 output_buffer,
                                 "{}",
-                                format!("Value of {word:?}: {value}").blue()
+                                format!("Value of {word}: {value}").blue()
                             )
                             .unwrap();
                         }
@@ -377,22 +406,17 @@ fn main() {
                             writeln!(
                                 output_buffer,
                                 "{}",
-                                format!("{word:?}: {value}\n").blue().bold()
+                                format!("{word}: {value}\n").blue().bold()
                             )
                             .unwrap();
                         } else {
-                            writeln!(output_buffer, "{}", format!("{word:?}: {value}\n").blue())
+                            writeln!(output_buffer, "{}", format!("{word}: {value}\n").blue())
                                 .unwrap();
                         }
                     }
                     if !args.less && !args.raw {
-                        for (idx, letter) in word.chars().enumerate() {
-                            writeln!(
-                                output_buffer,
-                                "{}",
-                                format!("{letter:?}: {}", letter_values[idx]).dark_cyan()
-                            )
-                            .unwrap();
+                        for letter in &word_value.letters {
+                            writeln!(output_buffer, "{}", letter_line(letter).dark_cyan()).unwrap();
                         }
                     }
                 }
@@ -433,119 +457,62 @@ fn main() {
 
                 output_buffer.flush().unwrap();
                 let mut input = String::new();
-                stdin()
+                let read = stdin()
                     .read_line(&mut input)
                     .expect("error: Failed to read line");
+                // Stop at the end of input instead of scoring empty lines forever
+                if read == 0 {
+                    writeln!(output_buffer).unwrap();
+                    output_buffer.flush().unwrap();
+                    process::exit(0);
+                }
 
                 // Trim the trailing newline character
                 input = input.trim().to_string();
 
                 // Split the input string into multiple strings
-                words = input.split_whitespace().map(|s| s.to_string()).collect();
+                words = tokenizer.split(&input);
 
                 // Reset values
                 values.clear();
             }
         }
-    } else if args.fast || args.json || cfg!(windows) {
-        if args.json {
-            let mut json_output: Vec<Value> = vec![];
-            let mut total = 0;
-
-            for word in &args.words {
-                let mut word_json = HashMap::new();
-                let mut value: u32 = 0;
-
-                for letter in word.chars() {
-                    let val = evaluate(letter);
-                    value += val as u32;
-                }
-                total += value;
-                word_json.insert(word, value);
-                json_output.push(json!(word_json));
-            }
-            if !args.no_total {
-                let mut word_json = HashMap::new();
-                word_json.insert("TOTAL_VALUE", total);
-                json_output.push(json!(word_json));
-            }
-
-            let json_string = serde_json::to_string_pretty(&json_output).unwrap();
-            writeln!(output_buffer, "{}", json_string).unwrap();
-        } else {
-            for word in &args.words {
-                let mut value: u32 = 0;
-                let mut letter_values: Vec<u8> = vec![];
-                for letter in word.chars() {
-                    let val = evaluate(letter);
-                    letter_values.push(val);
-                    value += val as u32;
-                }
-                values.push(value);
-                if !args.raw {
-                    if decorations {
-                        writeln!(
-                            output_buffer,
-                            "{}",
-                            format!("Value of {word:?}: {value}").blue().bold()
-                        )
-                        .unwrap();
-                    } else {
-                        writeln!(
-                            output_buffer,
-                            "{}",
-                            format!("Value of {word:?}: {value}").blue()
-                        )
-                        .unwrap();
-                    }
-                } else {
-                    if decorations {
-                        writeln!(
-                            output_buffer,
-                            "{}",
-                            format!("{word:?}: {value}").blue().bold()
-                        )
-                        .unwrap();
-                    } else {
-                        writeln!(output_buffer, "{}", format!("{word:?}: {value}").blue()).unwrap();
-                    }
-                }
-                if !args.less && !args.raw {
-                    for (idx, letter) in word.chars().enumerate() {
-                        writeln!(
-                            output_buffer,
-                            "{}",
-                            format!("{letter:?}: {}", letter_values[idx]).dark_cyan()
-                        )
-                        .unwrap();
-                    }
-                }
-            }
-            if !args.no_total {
-                if !args.raw {
-                    if decorations {
-                        writeln!(output_buffer, "{}", "-".repeat(20).blue().bold()).unwrap();
-                    } else {
-                        writeln!(output_buffer, "{}", "-".repeat(20).blue()).unwrap();
-                    }
-                }
-                let mut total = 0;
-                for value in values {
-                    total += value;
-                }
-                if decorations {
-                    writeln!(
-                        output_buffer,
-                        "{}",
-                        format!("Total Value: {total}").blue().bold()
-                    )
-                    .unwrap();
-                } else {
-                    writeln!(output_buffer, "{}", format!("Total Value: {total}").blue()).unwrap();
-                }
+    } else if !interactive
+        || args.fast
+        || args.output.is_some()
+        || args.sqlite.is_some()
+        || args.output_format() != OutputFormat::Text
+        || templates.is_active()
+        || matches!(args.layout, WordLayout::Grid)
+        || cfg!(windows)
+    {
+        let mut report = Report::new(
+            &mut output_buffer,
+            &args,
+            decorations,
+            &evaluator,
+            &templates,
+        )
+        .unwrap_or_else(|e| {
+            eprintln!("error: {e}");
+            process::exit(1);
+        });
+        let words = tokenizer.split_all(&args.words);
+        if let Err(e) = report.check_words(&words) {
+            eprintln!("error: {e}");
+            process::exit(1);
+        }
+        for word in &words {
+            if let Err(e) = report.write_word(word) {
+                report.close();
+                eprintln!("error: {e}");
+                process::exit(1);
             }
         }
-        output_buffer.flush().unwrap();
+        if let Err(e) = report.finish() {
+            eprintln!("error: {e}");
+            process::exit(1);
+        }
     } else {
         #[cfg(not(target_os = "windows"))]
         {
@@ -556,7 +523,7 @@ fn main() {
             execute!(io::stdout(), terminal::Clear(terminal::ClearType::All))
                 .expect("error: Failed to clear screen");
 
-            let mut words = args.words.clone();
+            let mut words = tokenizer.split_all(&args.words);
             setup_ctrl_c_handler();
             let ascii_art = r#"
              _   _ _   _ __  __ _____ ____      _    ____    _    _     ____
@@ -588,28 +555,27 @@ fn main() {
                 }
             }
             loop {
+                if report_unknown_chars(&args, &evaluator, &words).is_none() {
+                    words.clear();
+                }
                 for word in &words {
-                    let mut value: u32 = 0;
-                    let mut letter_values: Vec<u8> = vec![];
-                    for letter in word.chars() {
-                        let val = evaluate(letter);
-                        letter_values.push(val);
-                        value += val as u32;
-                    }
+                    let word_value = evaluator.evaluate_word(word);
+                    let value = word_value.value;
+                    let word = word_label(word, &word_value);
                     values.push(value);
                     if !args.raw {
                         if decorations {
                             writeln!(
                                 output_buffer,
                                 "{}",
-                                format!("Value of {word:?}: {value}").blue().bold()
+                                format!("Value of {word}: {value}").blue().bold()
                             )
                             .unwrap();
                         } else {
                             writeln!(
                                 output_buffer,
                                 "{}",
-                                format!("Value of {word:?}: {value}").blue()
+                                format!("Value of {word}: {value}").blue()
                             )
                             .unwrap();
                         }
@@ -618,22 +584,17 @@ fn main() {
                             writeln!(
                                 output_buffer,
                                 "{}",
-                                format!("{word:?}: {value}\n").blue().bold()
+                                format!("{word}: {value}\n").blue().bold()
                             )
                             .unwrap();
                         } else {
-                            writeln!(output_buffer, "{}", format!("{word:?}: {value}\n").blue())
+                            writeln!(output_buffer, "{}", format!("{word}: {value}\n").blue())
                                 .unwrap();
                         }
                     }
                     if !args.less && !args.raw {
-                        for (idx, letter) in word.chars().enumerate() {
-                            writeln!(
-                                output_buffer,
-                                "{}",
-                                format!("{letter:?}: {}", letter_values[idx]).dark_cyan()
-                            )
-                            .unwrap();
+                        for letter in &word_value.letters {
+                            writeln!(output_buffer, "{}", letter_line(letter).dark_cyan()).unwrap();
                         }
                     }
                 }
@@ -674,15 +635,22 @@ fn main() {
 
                 output_buffer.flush().unwrap();
                 let mut input = String::new();
-                stdin()
+                let read = stdin()
                     .read_line(&mut input)
                     .expect("error: Failed to read line");
+                // Stop at the end of input instead of scoring empty lines forever
+                if read == 0 {
+                    #[cfg(not(target_os = "windows"))]
+                    execute!(io::stdout(), LeaveAlternateScreen)
+                        .expect("error: Failed to leave alternate buffer");
+                    process::exit(0);
+                }
 
                 // Trim the trailing newline character
                 input = input.trim().to_string();
 
                 // Split the input string into multiple strings
-                words = input.split_whitespace().map(|s| s.to_string()).collect();
+                words = tokenizer.split(&input);
 
                 // Reset values
                 values.clear();
@@ -695,12 +663,13 @@ fn main() {
 #[cfg(not(target_os = "windows"))]
 fn setup_ctrl_c_handler() {
     // Use signal-hook crate to handle Ctrl+C signal
-    let mut signals = signal_hook::iterator::Signals::new(&[signal_hook::consts::SIGINT])
+    let mut signals = signal_hook::iterator::Signals::new([signal_hook::consts::SIGINT])
         .expect("error: Failed to setup Ctrl+C handler");
 
     // Spawn a separate thread to handle the signal
     thread::spawn(move || {
-        for _ in signals.forever() {
+        // Block until the first Ctrl+C arrives
+        if signals.forever().next().is_some() {
             // Clear the terminal again before exiting
             if let Err(err) = execute!(io::stdout(), terminal::Clear(terminal::ClearType::All)) {
                 eprintln!("error: Failed to clear terminal: {err}");
@@ -715,18 +684,3 @@ fn setup_ctrl_c_handler() {
         }
     });
 }
-
-/// Read the Words from <stdin>
-fn read_file_to_vector() -> Result<Vec<String>, io::Error> {
-    let reader = BufReader::new(stdin().lock());
-    let mut words = Vec::new();
-
-    for line in reader.lines() {
-        let line = line?;
-        for word in line.split_whitespace() {
-            words.push(word.to_string());
-        }
-    }
-
-    Ok(words)
-}
@@ -0,0 +1,237 @@
+use std::io::Write;
+
+use crate::args::OutputFormat;
+use crate::data::VALUE_TABLE;
+use crate::evaluate::{reduce, LetterValue};
+
+/// Inline style sheet mirroring the terminal colors: blue values, cyan letters,
+/// green titles and yellow notes on a dark background
+const STYLE: &str = "\
+body { background: #1e1e1e; color: #d4d4d4; font-family: ui-monospace, Menlo, Consolas, monospace; margin: 2em; }
+h1 { color: #23d18b; font-size: 1.4em; }
+table { border-collapse: collapse; }
+th { color: #23d18b; text-align: left; border-bottom: 1px solid #23d18b; padding: 0.3em 0.8em; }
+td { padding: 0.3em 0.8em; vertical-align: middle; }
+td.word, td.character { color: #11a8cd; }
+td.value { color: #3b8eea; font-weight: bold; text-align: right; }
+tr.total td { border-top: 1px solid #3b8eea; font-weight: bold; }
+.tile { display: inline-block; min-width: 1.6em; margin: 0 0.15em; padding: 0.15em 0.3em; border-radius: 0.3em; text-align: center; color: #1e1e1e; }
+.tile small { display: block; font-size: 0.7em; }
+.v0 { background: #6e6e6e; } .v1 { background: #f14c4c; } .v2 { background: #f5a142; }
+.v3 { background: #e5e510; } .v4 { background: #23d18b; } .v5 { background: #29b8db; }
+.v6 { background: #3b8eea; } .v7 { background: #b482f0; } .v8 { background: #d670d6; }
+.v9 { background: #e0e0e0; }
+p.note { color: #cdcd00; font-style: italic; }
+";
+
+/// Escape text for a GitHub-flavored Markdown table cell
+pub fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '\\' | '|' | '*' | '_' | '`' | '<' | '>' | '[' | ']' => {
+                escaped.push('\\');
+                escaped.push(character);
+            }
+            '\n' | '\r' => escaped.push(' '),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+/// Escape text for HTML
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+/// The header of a Markdown table of word values
+pub fn markdown_header(less: bool) -> String {
+    if less {
+        "| Type | Word | Value | Reduced |\n| --- | --- | ---: | ---: |\n".to_string()
+    } else {
+        "| Type | Word | Value | Reduced | Letters |\n| --- | --- | ---: | ---: | --- |\n"
+            .to_string()
+    }
+}
+
+/// A row of a Markdown table of word values (`kind` is `word`, `file` or `total`),
+/// in bold for totals
+pub fn markdown_row(
+    kind: &str,
+    label: &str,
+    value: u64,
+    letters: &[LetterValue],
+    less: bool,
+) -> String {
+    let label = escape_markdown(label);
+    let reduced = reduce(value, true);
+    let mut row = if kind == "word" {
+        format!("| {kind} | {label} | {value} | {reduced} |")
+    } else if label.is_empty() {
+        format!("| **{kind}** | | **{value}** | **{reduced}** |")
+    } else {
+        format!("| **{kind}** | **{label}** | **{value}** | **{reduced}** |")
+    };
+    if !less {
+        let letters: Vec<String> = letters
+            .iter()
+            .map(|letter| format!("{}={}", escape_markdown(&letter.text), letter.value))
+            .collect();
+        row.push_str(&format!(" {} |", letters.join(" ")));
+    }
+    row.push('\n');
+    row
+}
+
+/// The start of a self-contained HTML page, up to the body of a table with these headers
+pub fn html_start(title: &str, headers: &[&str]) -> String {
+    let headers: String = headers
+        .iter()
+        .map(|header| format!("<th>{}</th>", escape_html(header)))
+        .collect();
+    // A table without headers (like the chart grid) has no head at all
+    let head = if headers.is_empty() {
+        String::new()
+    } else {
+        format!("<thead><tr>{headers}</tr></thead>\n")
+    };
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n{STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n<table>\n{head}<tbody>\n",
+        title = escape_html(title)
+    )
+}
+
+/// The end of the table and of the HTML page, with an optional note
+pub fn html_end(note: Option<&str>) -> String {
+    html_end_with(&[], note)
+}
+
+/// The end of the table and of the HTML page, with paragraphs and an optional note
+fn html_end_with(paragraphs: &[String], note: Option<&str>) -> String {
+    let paragraphs: String = paragraphs
+        .iter()
+        .map(|paragraph| format!("<p>{}</p>\n", escape_html(paragraph)))
+        .collect();
+    let note = note
+        .map(|note| format!("<p class=\"note\">{}</p>\n", escape_html(note)))
+        .unwrap_or_default();
+    format!("</tbody>\n</table>\n{paragraphs}{note}</body>\n</html>\n")
+}
+
+/// A Markdown table with these headers and (unescaped) cells
+pub fn markdown_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut table = format!(
+        "| {} |\n|{}|\n",
+        headers.join(" | "),
+        vec![" --- "; headers.len()].join("|")
+    );
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|cell| escape_markdown(cell)).collect();
+        table.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    table
+}
+
+/// A self-contained HTML page with a table of these headers and (unescaped) cells,
+/// followed by paragraphs and an optional note
+pub fn html_table(
+    title: &str,
+    headers: &[&str],
+    rows: &[Vec<String>],
+    paragraphs: &[String],
+    note: Option<&str>,
+) -> String {
+    let mut page = html_start(title, headers);
+    for row in rows {
+        let cells: String = row
+            .iter()
+            .map(|cell| format!("<td>{}</td>", escape_html(cell)))
+            .collect();
+        page.push_str(&format!("<tr>{cells}</tr>\n"));
+    }
+    page.push_str(&html_end_with(paragraphs, note));
+    page
+}
+
+/// A row of an HTML table of word values (`kind` is `word`, `file` or `total`),
+/// with the letters as tiles colored by value
+pub fn html_row(
+    kind: &str,
+    label: &str,
+    value: u64,
+    letters: &[LetterValue],
+    less: bool,
+) -> String {
+    let class = if kind == "word" {
+        ""
+    } else {
+        " class=\"total\""
+    };
+    let mut row = format!(
+        "<tr{class}><td>{kind}</td><td class=\"word\">{}</td><td class=\"value\">{value}</td><td class=\"value\">{}</td>",
+        escape_html(label),
+        reduce(value, true)
+    );
+    if !less {
+        let tiles: String = letters
+            .iter()
+            .map(|letter| {
+                format!(
+                    "<span class=\"tile v{}\">{}<small>{}</small></span>",
+                    letter.value % 10,
+                    escape_html(&letter.text),
+                    letter.value
+                )
+            })
+            .collect();
+        row.push_str(&format!("<td class=\"letters\">{tiles}</td>"));
+    }
+    row.push_str("</tr>\n");
+    row
+}
+
+/// Write the value table as Markdown or HTML
+pub fn write_table(output_buffer: &mut impl Write, format: OutputFormat, note: Option<&str>) {
+    if format == OutputFormat::Html {
+        write!(
+            output_buffer,
+            "{}",
+            html_start("numeracalc value table", &["Character", "Value"])
+        )
+        .unwrap();
+        for (character, value) in &VALUE_TABLE {
+            writeln!(
+                output_buffer,
+                "<tr><td class=\"character\">{}</td><td class=\"value\"><span class=\"tile v{value}\">{value}</span></td></tr>",
+                escape_html(&character.to_string())
+            )
+            .unwrap();
+        }
+        write!(output_buffer, "{}", html_end(note)).unwrap();
+    } else {
+        writeln!(output_buffer, "| Character | Value |\n| --- | ---: |").unwrap();
+        for (character, value) in &VALUE_TABLE {
+            writeln!(
+                output_buffer,
+                "| {} | {value} |",
+                escape_markdown(&character.to_string())
+            )
+            .unwrap();
+        }
+        if let Some(note) = note {
+            writeln!(output_buffer, "\n*{note}*").unwrap();
+        }
+    }
+}
@@ -0,0 +1,797 @@
+use crossterm::style::Stylize;
+use serde_json::{json, Map, Value};
+use std::io::Write;
+
+use crate::args::{OutputFormat, WordLayout, CLI};
+use crate::evaluate::{
+    letter_label, letter_line, quote, reduce, word_label, Evaluator, LetterValue, UnknownChar,
+    WordValue,
+};
+use crate::grid::{self, GridEntry};
+use crate::markup;
+use crate::sqlite::SqliteReport;
+use crate::style::decorate;
+use crate::template::{self, FieldValue, Templates};
+use crate::xlsx::XlsxReport;
+
+/// Version of the JSON output, increased whenever a field changes meaning or is removed
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The numerology system the values are calculated with
+pub const SYSTEM: &str = "chaldean";
+
+/// A row of the tabular formats: a word, a file total or the total
+struct TableRow<'r> {
+    /// `word`, `file` or `total`, as in the NDJSON output
+    kind: &'static str,
+    /// The word, the file of a file total, or empty for the total
+    label: String,
+    value: u64,
+    /// Letters of the word (none for totals)
+    letters: &'r [LetterValue],
+}
+
+/// Writes the values of words as they are evaluated, keeping only running totals
+/// so that arbitrarily long input is handled in constant memory
+pub struct Report<'a, W: Write> {
+    output_buffer: &'a mut W,
+    args: &'a CLI,
+    decorations: bool,
+    evaluator: &'a Evaluator,
+    templates: &'a Templates,
+    format: OutputFormat,
+    /// File the current words are read from
+    file: Option<String>,
+    file_total: u64,
+    total: u64,
+    /// Number of words evaluated
+    words: usize,
+    /// Whether a word was already written to the JSON output (and the next needs a comma)
+    json_started: bool,
+    /// Totals of the files read, for the JSON, YAML and TOML output
+    file_totals: Vec<(String, u64)>,
+    /// Workbook for `--output`, written alongside the normal output
+    xlsx: Option<XlsxReport>,
+    /// Database for `--sqlite`, written alongside the normal output
+    sqlite: Option<SqliteReport>,
+    /// Words and totals for `--layout grid`, which is written all at once
+    grid: Vec<GridEntry>,
+    /// Words for the YAML, TOML and `--template-file` output, which are written all at once
+    documents: Vec<Value>,
+    /// Whether the header of a tabular format was already written
+    header_written: bool,
+    unknown_chars: Vec<UnknownChar>,
+}
+
+impl<'a, W: Write> Report<'a, W> {
+    pub fn new(
+        output_buffer: &'a mut W,
+        args: &'a CLI,
+        decorations: bool,
+        evaluator: &'a Evaluator,
+        templates: &'a Templates,
+    ) -> Result<Report<'a, W>, String> {
+        let xlsx = args.output.as_deref().map(XlsxReport::new).transpose()?;
+        let sqlite = args
+            .sqlite
+            .as_deref()
+            .map(|path| SqliteReport::new(path, args))
+            .transpose()?;
+        Ok(Report {
+            output_buffer,
+            args,
+            decorations,
+            evaluator,
+            templates,
+            format: args.output_format(),
+            file: None,
+            file_total: 0,
+            total: 0,
+            words: 0,
+            json_started: false,
+            file_totals: vec![],
+            documents: vec![],
+            grid: vec![],
+            xlsx,
+            sqlite,
+            header_written: false,
+            unknown_chars: vec![],
+        })
+    }
+
+    /// Write a row of the tabular formats (CSV, TSV, Markdown and HTML), preceded
+    /// by the header for the first row
+    ///
+    /// The first column tells word rows from file totals and the total. With
+    /// --less the letter breakdown (the last column) is left out.
+    fn write_row(&mut self, row: Option<TableRow>) {
+        let less = self.args.less;
+        match self.format {
+            OutputFormat::Markdown => {
+                if !self.header_written {
+                    write!(self.output_buffer, "{}", markup::markdown_header(less)).unwrap();
+                    self.header_written = true;
+                }
+                if let Some(row) = row {
+                    let row =
+                        markup::markdown_row(row.kind, &row.label, row.value, row.letters, less);
+                    write!(self.output_buffer, "{row}").unwrap();
+                }
+            }
+            OutputFormat::Html => {
+                if !self.header_written {
+                    let headers = ["Type", "Word", "Value", "Reduced", "Letters"];
+                    let headers = &headers[..if less { 4 } else { 5 }];
+                    let start = markup::html_start("numeracalc", headers);
+                    write!(self.output_buffer, "{start}").unwrap();
+                    self.header_written = true;
+                }
+                if let Some(row) = row {
+                    let row = markup::html_row(row.kind, &row.label, row.value, row.letters, less);
+                    write!(self.output_buffer, "{row}").unwrap();
+                }
+            }
+            _ => {
+                let delimiter = if self.format == OutputFormat::Tsv {
+                    b'\t'
+                } else {
+                    b','
+                };
+                let columns = if less { 4 } else { 5 };
+                let mut writer = csv::WriterBuilder::new()
+                    .delimiter(delimiter)
+                    .from_writer(vec![]);
+                if !self.header_written {
+                    writer
+                        .write_record(&["type", "word", "value", "reduced", "letters"][..columns])
+                        .unwrap();
+                    self.header_written = true;
+                }
+                if let Some(row) = row {
+                    let letters: Vec<String> = row
+                        .letters
+                        .iter()
+                        .map(|letter| format!("{}={}", letter.text, letter.value))
+                        .collect();
+                    let record = [
+                        row.kind.to_string(),
+                        row.label,
+                        row.value.to_string(),
+                        reduce(row.value, true).to_string(),
+                        letters.join(" "),
+                    ];
+                    writer.write_record(&record[..columns]).unwrap();
+                }
+                self.output_buffer
+                    .write_all(&writer.into_inner().unwrap())
+                    .unwrap();
+            }
+        }
+    }
+
+    /// Write a compact JSON object on its own line and flush it right away
+    fn write_json_line(&mut self, object: &Value) {
+        writeln!(self.output_buffer, "{object}").unwrap();
+        self.flush();
+    }
+
+    /// The fields that follow the words: the file totals, the total and the
+    /// unscored characters
+    fn summary_fields(&self) -> Map<String, Value> {
+        let mut fields = Map::new();
+        if !self.file_totals.is_empty() {
+            let files = self
+                .file_totals
+                .iter()
+                .map(|(file, total)| json!({ "file": file, "total": total }))
+                .collect();
+            fields.insert("files".to_string(), files);
+        }
+        if !self.args.no_total {
+            fields.insert("total".to_string(), json!(self.total));
+        }
+        if self.args.warn_unknown {
+            fields.insert(
+                "unknown_chars".to_string(),
+                unknown_chars_json(&self.unknown_chars),
+            );
+        }
+        fields
+    }
+
+    /// The whole data model of the JSON output, for the formats written all at once
+    fn document(&mut self) -> Value {
+        let mut document = Map::new();
+        document.insert("schema_version".to_string(), json!(SCHEMA_VERSION));
+        document.insert("system".to_string(), json!(SYSTEM));
+        let words = std::mem::take(&mut self.documents);
+        document.insert("words".to_string(), Value::Array(words));
+        document.extend(self.summary_fields());
+        Value::Object(document)
+    }
+
+    /// Whether the words are laid out with `--layout grid`
+    fn is_grid(&self) -> bool {
+        self.format == OutputFormat::Text && matches!(self.args.layout, WordLayout::Grid)
+    }
+
+    /// Write the start of the JSON object, up to the `words` array
+    fn write_json_header(&mut self) {
+        write!(
+            self.output_buffer,
+            "{{\n  \"schema_version\": {SCHEMA_VERSION},\n  \"system\": \"{SYSTEM}\",\n  \"words\": "
+        )
+        .unwrap();
+    }
+
+    /// Write a word of the JSON `words` array, formatted as in a pretty-printed object
+    fn write_json_word(&mut self, word: &Value) {
+        if self.json_started {
+            writeln!(self.output_buffer, ",").unwrap();
+        } else {
+            self.write_json_header();
+            writeln!(self.output_buffer, "[").unwrap();
+            self.json_started = true;
+        }
+        let word = serde_json::to_string_pretty(word).unwrap();
+        let lines: Vec<String> = word.lines().map(|line| format!("    {line}")).collect();
+        write!(self.output_buffer, "{}", lines.join("\n")).unwrap();
+    }
+
+    /// The fields of a word shared by the JSON and NDJSON output
+    fn word_object(&self, word: &str, word_value: &WordValue) -> Map<String, Value> {
+        let mut object = Map::new();
+        object.insert("text".to_string(), json!(word));
+        if let Some(transliterated) = &word_value.transliterated {
+            object.insert("transliterated".to_string(), json!(transliterated));
+        }
+        object.insert("value".to_string(), json!(word_value.value));
+        object.insert("reduced".to_string(), json!(reduce(word_value.value, true)));
+        if let Some(file) = &self.file {
+            object.insert("file".to_string(), json!(file));
+        }
+        if !self.args.less {
+            let letters = word_value
+                .letters
+                .iter()
+                .map(|letter| {
+                    json!({
+                        "char": letter.text,
+                        "value": letter.value,
+                        "start": letter.span.start,
+                        "end": letter.span.end,
+                    })
+                })
+                .collect();
+            object.insert("letters".to_string(), letters);
+        }
+        object
+    }
+
+    /// Start the words of a file
+    pub fn begin_file(&mut self, file: &str) {
+        if self.format == OutputFormat::Text && !self.is_grid() {
+            writeln!(
+                self.output_buffer,
+                "{}",
+                decorate(format!("==> {file} <==").green(), self.decorations)
+            )
+            .unwrap();
+        }
+        self.file = Some(file.to_string());
+        self.file_total = 0;
+    }
+
+    /// Finish the words of a file, writing its total
+    pub fn end_file(&mut self) {
+        let Some(file) = self.file.take() else {
+            return;
+        };
+        if let Some(xlsx) = &mut self.xlsx {
+            xlsx.add_file_total(&file, self.file_total);
+        }
+        if self.templates.is_active() {
+            self.file_totals.push((file, self.file_total));
+            return;
+        }
+        if self.args.no_total {
+            return;
+        }
+        match self.format {
+            OutputFormat::Json | OutputFormat::Yaml | OutputFormat::Toml => {
+                self.file_totals.push((file, self.file_total))
+            }
+            OutputFormat::Ndjson => {
+                let file_total = json!({ "type": "file", "file": file, "total": self.file_total });
+                self.write_json_line(&file_total);
+            }
+            OutputFormat::Csv | OutputFormat::Tsv | OutputFormat::Markdown | OutputFormat::Html => {
+                self.write_row(Some(TableRow {
+                    kind: "file",
+                    label: file,
+                    value: self.file_total,
+                    letters: &[],
+                }));
+            }
+            OutputFormat::Text if self.is_grid() => {
+                let label = format!("File Total: {file}");
+                self.grid.push(GridEntry::Total(label, self.file_total));
+            }
+            OutputFormat::Text => {
+                writeln!(
+                    self.output_buffer,
+                    "{}",
+                    decorate(
+                        format!("File Total: {}", self.file_total).blue(),
+                        self.decorations
+                    )
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    /// Evaluate a word and write its value
+    ///
+    /// Fails with `--strict` if the word has characters that are not in the table.
+    pub fn word(&mut self, word: &str) -> Result<(), String> {
+        self.check_words(&[word.to_string()])?;
+        self.write_word(word)
+    }
+
+    /// Report the unscored characters of words before any of them is written
+    ///
+    /// Fails with `--strict` if any word has characters that are not in the table,
+    /// naming every such word.
+    pub fn check_words(&mut self, words: &[String]) -> Result<(), String> {
+        match report_unknown_chars(self.args, self.evaluator, words) {
+            Some(unknown_chars) => {
+                self.unknown_chars.extend(unknown_chars);
+                Ok(())
+            }
+            None => {
+                let mut unscored: Vec<String> = vec![];
+                for unknown in self.evaluator.unknown_chars(words) {
+                    let word = quote(&unknown.word);
+                    if !unscored.contains(&word) {
+                        unscored.push(word);
+                    }
+                }
+                Err(format!("Unscored characters in {}", unscored.join(", ")))
+            }
+        }
+    }
+
+    /// Evaluate a word already passed to [`Report::check_words`] and write its value
+    pub fn write_word(&mut self, word: &str) -> Result<(), String> {
+        let word = word.to_string();
+        let word_value = self.evaluator.evaluate_word(&word);
+        let value = word_value.value;
+        self.file_total += value;
+        self.total += value;
+        self.words += 1;
+        if let Some(xlsx) = &mut self.xlsx {
+            xlsx.add_word(&word, self.file.as_deref(), &word_value)?;
+        }
+        if let Some(sqlite) = &mut self.sqlite {
+            sqlite.add_word(&word, self.file.as_deref(), &word_value)?;
+        }
+
+        if self.templates.report.is_some() {
+            let word_json = Value::Object(self.word_object(&word, &word_value));
+            self.documents.push(word_json);
+            return Ok(());
+        }
+        if self.templates.is_active() {
+            if let Some(template) = &self.templates.word {
+                let letters: Vec<String> = word_value
+                    .letters
+                    .iter()
+                    .map(|letter| format!("{}={}", letter.text, letter.value))
+                    .collect();
+                let line = template.render(&[
+                    ("word", FieldValue::Text(word.clone())),
+                    ("value", FieldValue::Number(value)),
+                    ("reduced", FieldValue::Number(reduce(value, true).into())),
+                    ("letters", FieldValue::Text(letters.join(" "))),
+                    (
+                        "transliterated",
+                        FieldValue::Text(word_value.transliterated.clone().unwrap_or_default()),
+                    ),
+                    (
+                        "file",
+                        FieldValue::Text(self.file.clone().unwrap_or_default()),
+                    ),
+                    ("index", FieldValue::Number(self.words as u64)),
+                ]);
+                writeln!(self.output_buffer, "{line}").unwrap();
+            }
+            return Ok(());
+        }
+
+        if self.format == OutputFormat::Ndjson {
+            let mut word_json = Map::new();
+            word_json.insert("type".to_string(), json!("word"));
+            word_json.extend(self.word_object(&word, &word_value));
+            self.write_json_line(&Value::Object(word_json));
+            return Ok(());
+        }
+        if self.format.is_tabular() {
+            self.write_row(Some(TableRow {
+                kind: "word",
+                label: word,
+                value,
+                letters: &word_value.letters,
+            }));
+            return Ok(());
+        }
+        if self.format == OutputFormat::Json {
+            let word_json = Value::Object(self.word_object(&word, &word_value));
+            self.write_json_word(&word_json);
+            return Ok(());
+        }
+        if matches!(self.format, OutputFormat::Yaml | OutputFormat::Toml) {
+            let word_json = Value::Object(self.word_object(&word, &word_value));
+            self.documents.push(word_json);
+            return Ok(());
+        }
+
+        let label = word_label(&word, &word_value);
+        if self.is_grid() {
+            self.grid.push(GridEntry::Word(label, word_value));
+            return Ok(());
+        }
+        let line = if self.args.raw {
+            format!("{label}: {value}")
+        } else {
+            format!("Value of {label}: {value}")
+        };
+        writeln!(
+            self.output_buffer,
+            "{}",
+            decorate(line.blue(), self.decorations)
+        )
+        .unwrap();
+        if !self.args.less && !self.args.raw {
+            for letter in &word_value.letters {
+                writeln!(self.output_buffer, "{}", letter_line(letter).dark_cyan()).unwrap();
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the overall total (and the unscored characters) and flush the output
+    ///
+    /// Fails if the `--template-file` report cannot be rendered.
+    pub fn finish(&mut self) -> Result<(), String> {
+        self.end_file();
+        if let Some(source) = &self.templates.report {
+            let document = self.document();
+            let report = template::render_report(source, &document)?;
+            write!(self.output_buffer, "{report}").unwrap();
+        } else if self.templates.is_active() {
+            if let (Some(template), false) = (&self.templates.total, self.args.no_total) {
+                let line = template.render(&[
+                    ("total", FieldValue::Number(self.total)),
+                    (
+                        "reduced",
+                        FieldValue::Number(reduce(self.total, true).into()),
+                    ),
+                    ("words", FieldValue::Number(self.words as u64)),
+                ]);
+                writeln!(self.output_buffer, "{line}").unwrap();
+            }
+        } else if self.format == OutputFormat::Ndjson {
+            let mut summary = json!({ "type": "summary", "words": self.words });
+            if !self.args.no_total {
+                summary["total"] = json!(self.total);
+                summary["reduced"] = json!(reduce(self.total, true));
+            }
+            if self.args.warn_unknown {
+                summary["unknown_chars"] = unknown_chars_json(&self.unknown_chars);
+            }
+            self.write_json_line(&summary);
+        } else if self.format.is_tabular() {
+            if !self.args.no_total {
+                self.write_row(Some(TableRow {
+                    kind: "total",
+                    label: String::new(),
+                    value: self.total,
+                    letters: &[],
+                }));
+            }
+            self.write_row(None);
+            if self.format == OutputFormat::Html {
+                write!(self.output_buffer, "{}", markup::html_end(None)).unwrap();
+            }
+        } else if matches!(self.format, OutputFormat::Yaml | OutputFormat::Toml) {
+            let document = self.document();
+            write!(
+                self.output_buffer,
+                "{}",
+                serialize(&document, self.format).unwrap()
+            )
+            .unwrap();
+        } else if self.format == OutputFormat::Json {
+            if self.json_started {
+                write!(self.output_buffer, "\n  ]").unwrap();
+            } else {
+                self.write_json_header();
+                write!(self.output_buffer, "[]").unwrap();
+            }
+            // The fields after the words, written as the members of a pretty-printed object
+            let rest = self.summary_fields();
+            if !rest.is_empty() {
+                let rest = serde_json::to_string_pretty(&Value::Object(rest)).unwrap();
+                let members: Vec<&str> = rest.lines().collect();
+                write!(self.output_buffer, ",").unwrap();
+                for member in &members[1..members.len() - 1] {
+                    write!(self.output_buffer, "\n{member}").unwrap();
+                }
+            }
+            writeln!(self.output_buffer, "\n}}").unwrap();
+        } else if self.is_grid() {
+            if !self.args.no_total {
+                self.grid
+                    .push(GridEntry::Total("Total".to_string(), self.total));
+            }
+            grid::render(self.output_buffer, &self.grid, self.decorations);
+            self.grid.clear();
+        } else if !self.args.no_total {
+            if !self.args.raw {
+                writeln!(
+                    self.output_buffer,
+                    "{}",
+                    decorate("-".repeat(20).blue(), self.decorations)
+                )
+                .unwrap();
+            }
+            writeln!(
+                self.output_buffer,
+                "{}",
+                decorate(
+                    format!("Total Value: {}", self.total).blue(),
+                    self.decorations
+                )
+            )
+            .unwrap();
+        }
+        self.flush();
+        if let Some(xlsx) = self.xlsx.take() {
+            xlsx.save(self.total)?;
+        }
+        if let Some(sqlite) = self.sqlite.take() {
+            sqlite.finish(self.total)?;
+        }
+        Ok(())
+    }
+
+    /// Close the JSON or HTML document after the input failed part way, so that
+    /// the words written so far still form a valid document (nothing is written
+    /// if no word was)
+    pub fn close(&mut self) {
+        if !self.templates.is_active() {
+            match self.format {
+                OutputFormat::Json if self.json_started => {
+                    writeln!(self.output_buffer, "\n  ]\n}}").unwrap();
+                }
+                OutputFormat::Html if self.header_written => {
+                    write!(self.output_buffer, "{}", markup::html_end(None)).unwrap();
+                }
+                _ => {}
+            }
+        }
+        self.flush();
+    }
+
+    /// Flush what was written so far, so that piped output appears as it is computed
+    pub fn flush(&mut self) {
+        self.output_buffer.flush().unwrap();
+    }
+}
+
+/// Serialize a document as pretty-printed JSON, YAML or TOML
+pub fn serialize(document: &Value, format: OutputFormat) -> Result<String, String> {
+    match format {
+        OutputFormat::Yaml => serde_yaml::to_string(document).map_err(|e| e.to_string()),
+        OutputFormat::Toml => toml::to_string_pretty(document).map_err(|e| e.to_string()),
+        _ => serde_json::to_string_pretty(document)
+            .map(|json| json + "\n")
+            .map_err(|e| e.to_string()),
+    }
+}
+
+/// The JSON Schema (draft 2020-12) of the --json output
+pub fn json_schema() -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "numeracalc word values",
+        "type": "object",
+        "required": ["schema_version", "system", "words"],
+        "properties": {
+            "schema_version": {
+                "description": "Version of this schema",
+                "const": SCHEMA_VERSION,
+            },
+            "system": {
+                "description": "Numerology system the values are calculated with",
+                "const": SYSTEM,
+            },
+            "words": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["text", "value", "reduced"],
+                    "properties": {
+                        "text": {
+                            "description": "The word as written in the input",
+                            "type": "string",
+                        },
+                        "transliterated": {
+                            "description": "The word in Latin script (with --transliterate)",
+                            "type": "string",
+                        },
+                        "value": { "type": "integer", "minimum": 0 },
+                        "reduced": {
+                            "description": "The value reduced to a single digit or a master number",
+                            "type": "integer",
+                            "minimum": 0,
+                        },
+                        "file": {
+                            "description": "The file the word was read from (with --input)",
+                            "type": "string",
+                        },
+                        "letters": {
+                            "description": "Value of each letter (left out with --less)",
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "required": ["char", "value", "start", "end"],
+                                "properties": {
+                                    "char": { "type": "string" },
+                                    "value": { "type": "integer", "minimum": 0 },
+                                    "start": {
+                                        "description": "Byte offset of the letter in the word (or in its transliteration)",
+                                        "type": "integer",
+                                        "minimum": 0,
+                                    },
+                                    "end": {
+                                        "description": "Byte offset just past the letter",
+                                        "type": "integer",
+                                        "minimum": 0,
+                                    },
+                                },
+                            },
+                        },
+                    },
+                },
+            },
+            "files": {
+                "description": "Total of each file (with --input)",
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["file", "total"],
+                    "properties": {
+                        "file": { "type": "string" },
+                        "total": { "type": "integer", "minimum": 0 },
+                    },
+                },
+            },
+            "total": {
+                "description": "Sum of the values of all words (left out with --no-total)",
+                "type": "integer",
+                "minimum": 0,
+            },
+            "unknown_chars": {
+                "description": "Characters with no value in the table (with --warn-unknown)",
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["word", "char", "position"],
+                    "properties": {
+                        "word": { "type": "string" },
+                        "char": { "type": "string" },
+                        "position": { "type": "integer", "minimum": 1 },
+                    },
+                },
+            },
+        },
+    })
+}
+
+/// Report characters that have no value in the table
+///
+/// With `--strict` every unscored character is printed as an error and `None`
+/// is returned; with `--warn-unknown` they are printed as warnings.
+pub fn report_unknown_chars(
+    args: &CLI,
+    evaluator: &Evaluator,
+    words: &[String],
+) -> Option<Vec<UnknownChar>> {
+    if !args.strict && !args.warn_unknown {
+        return Some(vec![]);
+    }
+    let unknown_chars = evaluator.unknown_chars(words);
+    let level = if args.strict { "error" } else { "warning" };
+    for unknown in &unknown_chars {
+        eprintln!(
+            "{level}: Unscored character {} in {} at position {}",
+            letter_label(&unknown.character),
+            quote(&unknown.word),
+            unknown.position
+        );
+    }
+    if args.strict && !unknown_chars.is_empty() {
+        None
+    } else {
+        Some(unknown_chars)
+    }
+}
+
+/// Convert unscored characters into json
+pub fn unknown_chars_json(unknown_chars: &[UnknownChar]) -> Value {
+    unknown_chars
+        .iter()
+        .map(|unknown| {
+            json!({
+                "word": unknown.word,
+                "char": unknown.character,
+                "position": unknown.position,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// Report a single word with the given options
+    fn report(options: &[&str], word: &str) -> String {
+        let args = CLI::parse_from(std::iter::once("numeracalc").chain(options.iter().copied()));
+        let evaluator = Evaluator::new(&args).unwrap();
+        let templates = Templates::new(&args).unwrap();
+        let mut output = Vec::new();
+        let mut report = Report::new(&mut output, &args, false, &evaluator, &templates).unwrap();
+        report.write_word(word).unwrap();
+        report.close();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn text_breakdown_has_no_byte_offsets() {
+        let output = report(&[], "Müller");
+        assert!(output.contains("'ü': 6"), "{output}");
+        assert!(!output.contains("bytes"), "{output}");
+    }
+
+    #[test]
+    fn json_letters_have_byte_offsets() {
+        let output: Value = serde_json::from_str(&report(&["--json"], "Müller")).unwrap();
+        let offsets: Vec<(&str, u64, u64)> = output["words"][0]["letters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|letter| {
+                (
+                    letter["char"].as_str().unwrap(),
+                    letter["start"].as_u64().unwrap(),
+                    letter["end"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            offsets,
+            [
+                ("M", 0, 1),
+                ("ü", 1, 3),
+                ("l", 3, 4),
+                ("l", 4, 5),
+                ("e", 5, 6),
+                ("r", 6, 7)
+            ]
+        );
+    }
+}
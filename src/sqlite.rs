@@ -0,0 +1,220 @@
+use chrono::{SecondsFormat, Utc};
+use clap::ValueEnum;
+use rusqlite::{params, Connection};
+use serde_json::json;
+
+use crate::args::CLI;
+use crate::data::VALUE_TABLE;
+use crate::evaluate::{reduce, WordValue};
+use crate::output::SYSTEM;
+
+/// Version of the database schema, stored as the `user_version` of the database
+const SCHEMA_VERSION: i64 = 1;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS ciphers (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+CREATE TABLE IF NOT EXISTS cipher_values (
+    cipher_id INTEGER NOT NULL REFERENCES ciphers(id),
+    character TEXT NOT NULL,
+    value INTEGER NOT NULL,
+    PRIMARY KEY (cipher_id, character)
+);
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    started_at TEXT NOT NULL,
+    finished_at TEXT,
+    version TEXT NOT NULL,
+    cipher_id INTEGER NOT NULL REFERENCES ciphers(id),
+    settings TEXT NOT NULL,
+    word_count INTEGER,
+    total INTEGER
+);
+CREATE TABLE IF NOT EXISTS words (
+    id INTEGER PRIMARY KEY,
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    position INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    transliterated TEXT,
+    file TEXT,
+    value INTEGER NOT NULL,
+    reduced INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS words_run_id ON words(run_id);
+CREATE INDEX IF NOT EXISTS words_text ON words(text);
+CREATE TABLE IF NOT EXISTS letters (
+    word_id INTEGER NOT NULL REFERENCES words(id),
+    position INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    value INTEGER NOT NULL,
+    scored INTEGER NOT NULL,
+    span_start INTEGER NOT NULL,
+    span_end INTEGER NOT NULL,
+    PRIMARY KEY (word_id, position)
+);
+";
+
+/// Name of a command line value, as it is written on the command line
+fn value_name(value: &impl ValueEnum) -> Option<String> {
+    value
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+}
+
+/// The options that affect the values, recorded with each run
+fn settings(args: &CLI) -> String {
+    json!({
+        "digits": value_name(&args.digits),
+        "transliterate": args.transliterate.as_ref().and_then(value_name),
+        "locale": args.locale.as_ref().and_then(value_name),
+        "locale_file": args.locale_file,
+        "normalize": !args.no_normalize,
+        "delimiter": args.delimiter,
+        "split_regex": args.split_regex,
+        "null_data": args.null_data,
+        "hyphens": value_name(&args.hyphens),
+        "apostrophes": value_name(&args.apostrophes),
+        "input": args.input,
+    })
+    .to_string()
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// A run recorded in a SQLite database for `--sqlite`
+///
+/// Everything is written in one transaction, committed when the run finishes,
+/// so that a failed run leaves nothing behind.
+pub struct SqliteReport {
+    path: String,
+    connection: Connection,
+    run_id: i64,
+    /// Number of words recorded so far
+    position: i64,
+}
+
+impl SqliteReport {
+    /// Open or create the database and start a run
+    pub fn new(path: &str, args: &CLI) -> Result<SqliteReport, String> {
+        let error = |e: rusqlite::Error| format!("Failed to write {path:?}: {e}");
+        let connection = Connection::open(path).map_err(error)?;
+        let version: i64 = connection
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(error)?;
+        if version > SCHEMA_VERSION {
+            return Err(format!(
+                "{path:?} was written by a newer version (schema version {version})"
+            ));
+        }
+        connection
+            .execute_batch(&format!(
+                "BEGIN; {SCHEMA} PRAGMA user_version = {SCHEMA_VERSION};"
+            ))
+            .map_err(error)?;
+
+        connection
+            .execute(
+                "INSERT OR IGNORE INTO ciphers (name) VALUES (?1)",
+                params![SYSTEM],
+            )
+            .map_err(error)?;
+        let cipher_id: i64 = connection
+            .query_row(
+                "SELECT id FROM ciphers WHERE name = ?1",
+                params![SYSTEM],
+                |row| row.get(0),
+            )
+            .map_err(error)?;
+        for (character, value) in &VALUE_TABLE {
+            connection
+                .execute(
+                    "INSERT OR IGNORE INTO cipher_values (cipher_id, character, value) VALUES (?1, ?2, ?3)",
+                    params![cipher_id, character.to_string(), value],
+                )
+                .map_err(error)?;
+        }
+
+        connection
+            .execute(
+                "INSERT INTO runs (started_at, version, cipher_id, settings) VALUES (?1, ?2, ?3, ?4)",
+                params![now(), env!("CARGO_PKG_VERSION"), cipher_id, settings(args)],
+            )
+            .map_err(error)?;
+        let run_id = connection.last_insert_rowid();
+        Ok(SqliteReport {
+            path: path.to_string(),
+            connection,
+            run_id,
+            position: 0,
+        })
+    }
+
+    fn error(&self, e: rusqlite::Error) -> String {
+        format!("Failed to write {:?}: {e}", self.path)
+    }
+
+    /// Record a word and its letters
+    pub fn add_word(
+        &mut self,
+        word: &str,
+        file: Option<&str>,
+        word_value: &WordValue,
+    ) -> Result<(), String> {
+        self.position += 1;
+        self.connection
+            .prepare_cached(
+                "INSERT INTO words (run_id, position, text, transliterated, file, value, reduced)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )
+            .and_then(|mut statement| {
+                statement.execute(params![
+                    self.run_id,
+                    self.position,
+                    word,
+                    word_value.transliterated,
+                    file,
+                    // SQLite integers are signed 64-bit
+                    word_value.value as i64,
+                    reduce(word_value.value, true),
+                ])
+            })
+            .map_err(|e| self.error(e))?;
+        let word_id = self.connection.last_insert_rowid();
+        let mut statement = self
+            .connection
+            .prepare_cached(
+                "INSERT INTO letters (word_id, position, text, value, scored, span_start, span_end)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )
+            .map_err(|e| self.error(e))?;
+        for (index, letter) in word_value.letters.iter().enumerate() {
+            statement
+                .execute(params![
+                    word_id,
+                    index as i64 + 1,
+                    letter.text,
+                    letter.value,
+                    letter.scored,
+                    letter.span.start as i64,
+                    letter.span.end as i64,
+                ])
+                .map_err(|e| format!("Failed to write {:?}: {e}", self.path))?;
+        }
+        Ok(())
+    }
+
+    /// Record the totals of the run and commit it
+    pub fn finish(self, total: u64) -> Result<(), String> {
+        self.connection
+            .execute(
+                "UPDATE runs SET finished_at = ?1, word_count = ?2, total = ?3 WHERE id = ?4",
+                params![now(), self.position, total as i64, self.run_id],
+            )
+            .and_then(|_| self.connection.execute_batch("COMMIT"))
+            .map_err(|e| self.error(e))
+    }
+}
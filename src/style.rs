@@ -0,0 +1,11 @@
+use crossterm::style::{StyledContent, Stylize};
+use std::fmt::Display;
+
+/// Make styled content bold when decorations are enabled
+pub fn decorate<D: Display>(content: StyledContent<D>, decorations: bool) -> StyledContent<D> {
+    if decorations {
+        content.bold()
+    } else {
+        content
+    }
+}
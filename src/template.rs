@@ -0,0 +1,286 @@
+use minijinja::Environment;
+use serde_json::Value;
+use std::fs;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::args::CLI;
+use crate::tokenize::unescape;
+
+/// Fields of `--template`, for each word
+pub const WORD_FIELDS: [&str; 7] = [
+    "word",
+    "value",
+    "reduced",
+    "letters",
+    "transliterated",
+    "file",
+    "index",
+];
+
+/// Fields of `--total-template`
+pub const TOTAL_FIELDS: [&str; 3] = ["total", "reduced", "words"];
+
+/// Alignment of a padded field
+#[derive(Debug, Clone, Copy)]
+enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Literal(String),
+    Field {
+        name: String,
+        fill: char,
+        /// Numbers are aligned right and text left unless set
+        align: Option<Align>,
+        width: usize,
+    },
+}
+
+/// The value of a template field
+pub enum FieldValue {
+    Text(String),
+    Number(u64),
+}
+
+/// A one-line template like `{word:<12}\t{value:>3}`
+///
+/// Fields take an optional fill character, alignment (`<`, `^` or `>`) and width,
+/// as in Rust's format strings; `{{` and `}}` are literal braces.
+#[derive(Debug, Clone)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+/// Parse a field specification like `word:*^10`
+fn parse_field(spec: &str, fields: &[&str]) -> Result<Segment, String> {
+    let (name, format) = spec.split_once(':').unwrap_or((spec, ""));
+    let name = name.trim();
+    if !fields.contains(&name) {
+        return Err(format!(
+            "Unknown template field {{{name}}} (expected one of: {})",
+            fields.join(", ")
+        ));
+    }
+    let align_of = |character: char| match character {
+        '<' => Some(Align::Left),
+        '^' => Some(Align::Center),
+        '>' => Some(Align::Right),
+        _ => None,
+    };
+    let characters: Vec<char> = format.chars().collect();
+    let (fill, align, width) = match characters.as_slice() {
+        [fill, align, width @ ..] if align_of(*align).is_some() => (*fill, align_of(*align), width),
+        [align, width @ ..] if align_of(*align).is_some() => (' ', align_of(*align), width),
+        width => (' ', None, width),
+    };
+    let width: String = width.iter().collect();
+    let width = if width.is_empty() {
+        0
+    } else {
+        width
+            .parse()
+            .map_err(|_| format!("Invalid format {format:?} for template field {{{name}}}"))?
+    };
+    Ok(Segment::Field {
+        name: name.to_string(),
+        fill,
+        align,
+        width,
+    })
+}
+
+impl Template {
+    /// Parse a template, allowing only the given fields
+    pub fn parse(text: &str, fields: &[&str]) -> Result<Template, String> {
+        let text = unescape(text);
+        let mut segments = vec![];
+        let mut literal = String::new();
+        let mut characters = text.chars().peekable();
+        while let Some(character) = characters.next() {
+            match character {
+                '{' if characters.peek() == Some(&'{') => {
+                    characters.next();
+                    literal.push('{');
+                }
+                '}' if characters.peek() == Some(&'}') => {
+                    characters.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut spec = String::new();
+                    loop {
+                        match characters.next() {
+                            Some('}') => break,
+                            Some(character) => spec.push(character),
+                            None => return Err(format!("Unclosed {{ in template {text:?}")),
+                        }
+                    }
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(parse_field(&spec, fields)?);
+                }
+                '}' => return Err(format!("Unmatched }} in template {text:?}")),
+                _ => literal.push(character),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Ok(Template { segments })
+    }
+
+    /// Fill in the fields, leaving fields without a value empty
+    pub fn render(&self, values: &[(&str, FieldValue)]) -> String {
+        let mut rendered = String::new();
+        for segment in &self.segments {
+            let (name, fill, align, width) = match segment {
+                Segment::Literal(literal) => {
+                    rendered.push_str(literal);
+                    continue;
+                }
+                Segment::Field {
+                    name,
+                    fill,
+                    align,
+                    width,
+                } => (name, *fill, *align, *width),
+            };
+            let (text, numeric) = match values.iter().find(|(field, _)| field == name) {
+                Some((_, FieldValue::Text(text))) => (text.clone(), false),
+                Some((_, FieldValue::Number(number))) => (number.to_string(), true),
+                None => (String::new(), false),
+            };
+            // Pad by user-visible characters so accented and combined letters line up
+            let padding = width.saturating_sub(text.graphemes(true).count());
+            let align = align.unwrap_or(if numeric { Align::Right } else { Align::Left });
+            let (before, after) = match align {
+                Align::Left => (0, padding),
+                Align::Center => (padding / 2, padding - padding / 2),
+                Align::Right => (padding, 0),
+            };
+            rendered.extend(std::iter::repeat_n(fill, before));
+            rendered.push_str(&text);
+            rendered.extend(std::iter::repeat_n(fill, after));
+        }
+        rendered
+    }
+}
+
+/// The templates given on the command line
+#[derive(Debug, Clone, Default)]
+pub struct Templates {
+    pub word: Option<Template>,
+    pub total: Option<Template>,
+    /// Source of the `--template-file` report template
+    pub report: Option<String>,
+}
+
+impl Templates {
+    /// Parse `--template`, `--total-template` and `--template-file`
+    pub fn new(args: &CLI) -> Result<Templates, String> {
+        let report = match &args.template_file {
+            Some(path) => {
+                let source = fs::read_to_string(path)
+                    .map_err(|e| format!("Failed to read {path:?}: {e}"))?;
+                // Compile it now so that syntax errors are reported before any input is read
+                Environment::new()
+                    .template_from_str(&source)
+                    .map_err(|e| format!("Invalid template {path:?}: {e}"))?;
+                Some(source)
+            }
+            None => None,
+        };
+        Ok(Templates {
+            word: args
+                .template
+                .as_deref()
+                .map(|template| Template::parse(template, &WORD_FIELDS))
+                .transpose()?,
+            total: args
+                .total_template
+                .as_deref()
+                .map(|template| Template::parse(template, &TOTAL_FIELDS))
+                .transpose()?,
+            report,
+        })
+    }
+
+    /// Whether any template replaces the normal output
+    pub fn is_active(&self) -> bool {
+        self.word.is_some() || self.total.is_some() || self.report.is_some()
+    }
+}
+
+/// Render the `--template-file` report with the JSON data model as its context
+pub fn render_report(source: &str, document: &Value) -> Result<String, String> {
+    let mut environment = Environment::new();
+    environment.set_keep_trailing_newline(true);
+    let template = environment
+        .template_from_str(source)
+        .map_err(|e| format!("Invalid template: {e}"))?;
+    template
+        .render(document)
+        .map_err(|e| format!("Failed to render the template: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str, values: &[(&str, FieldValue)]) -> String {
+        Template::parse(template, &WORD_FIELDS)
+            .unwrap()
+            .render(values)
+    }
+
+    #[test]
+    fn renders_fields_and_literals() {
+        let values = [
+            ("word", FieldValue::Text("love".to_string())),
+            ("value", FieldValue::Number(21)),
+        ];
+        assert_eq!(render("{word} = {value}", &values), "love = 21");
+        assert_eq!(render("{word}\\t{value}\\n", &values), "love\t21\n");
+        // Fields without a value are left empty
+        assert_eq!(render("[{file}]", &values), "[]");
+    }
+
+    #[test]
+    fn escapes_braces() {
+        let values = [("value", FieldValue::Number(21))];
+        assert_eq!(render("{{{value}}}", &values), "{21}");
+        assert_eq!(render("{{value}}", &values), "{value}");
+    }
+
+    #[test]
+    fn pads_with_fill_align_and_width() {
+        let values = [
+            ("word", FieldValue::Text("love".to_string())),
+            ("value", FieldValue::Number(21)),
+        ];
+        // Text is aligned left and numbers right unless the alignment is given
+        assert_eq!(render("{word:8}|{value:5}", &values), "love    |   21");
+        assert_eq!(render("{word:>8}|{value:<5}", &values), "    love|21   ");
+        assert_eq!(render("{word:*^8}", &values), "**love**");
+        assert_eq!(render("{word:-^9}", &values), "--love---");
+        assert_eq!(render("{word:2}", &values), "love");
+        // Width counts user-visible characters
+        let values = [("word", FieldValue::Text("e\u{301}te\u{301}".to_string()))];
+        assert_eq!(render("{word:5}|", &values), "e\u{301}te\u{301}  |");
+    }
+
+    #[test]
+    fn rejects_invalid_templates() {
+        let parse = |template| Template::parse(template, &WORD_FIELDS).unwrap_err();
+        assert!(parse("{total}").starts_with("Unknown template field {total}"));
+        assert!(parse("{word").starts_with("Unclosed {"));
+        assert!(parse("word}").starts_with("Unmatched }"));
+        assert!(parse("{word:>x}").starts_with("Invalid format"));
+        assert!(Template::parse("{total:>6}", &TOTAL_FIELDS).is_ok());
+    }
+}
@@ -0,0 +1,256 @@
+use regex::Regex;
+
+use crate::args::{PunctuationRule, CLI};
+
+/// Hyphens and dashes that join the parts of a word
+const HYPHENS: [char; 3] = ['-', '‐', '‑'];
+/// Straight and typographic apostrophes
+const APOSTROPHES: [char; 3] = ['\'', '’', 'ʼ'];
+
+/// How the words of a line are separated
+#[derive(Debug, Clone)]
+enum Separator {
+    Whitespace,
+    /// The whole line is a single word
+    None,
+    Characters(Vec<char>),
+    Regex(Regex),
+}
+
+/// Splits input into words, the same way for arguments, stdin, files and prompts
+#[derive(Debug, Clone)]
+pub struct Tokenizer {
+    separator: Separator,
+    hyphens: PunctuationRule,
+    apostrophes: PunctuationRule,
+    /// Lines are separated by NUL instead of newline
+    pub null_data: bool,
+}
+
+/// Replace the escapes `\t`, `\n`, `\r`, `\0`, `\e` and `\\` in a delimiter or template
+///
+/// Any other escaped character stands for itself, and a trailing `\` is kept.
+pub fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut characters = text.chars();
+    while let Some(character) = characters.next() {
+        if character != '\\' {
+            unescaped.push(character);
+            continue;
+        }
+        match characters.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('0') => unescaped.push('\0'),
+            Some('e') => unescaped.push('\x1b'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+impl Tokenizer {
+    /// Create the tokenizer from the command line options
+    pub fn new(args: &CLI) -> Result<Tokenizer, String> {
+        let separator = if let Some(delimiter) = &args.delimiter {
+            let delimiter = unescape(delimiter);
+            if delimiter.is_empty() {
+                return Err("The delimiter cannot be empty".to_string());
+            }
+            Separator::Characters(delimiter.chars().collect())
+        } else if let Some(pattern) = &args.split_regex {
+            Separator::Regex(
+                Regex::new(pattern).map_err(|e| format!("Invalid split regex {pattern:?}: {e}"))?,
+            )
+        } else if args.null_data {
+            Separator::None
+        } else {
+            Separator::Whitespace
+        };
+        Ok(Tokenizer {
+            separator,
+            hyphens: args.hyphens.clone(),
+            apostrophes: args.apostrophes.clone(),
+            null_data: args.null_data,
+        })
+    }
+
+    /// Split a line into words, dropping empty ones
+    pub fn split(&self, line: &str) -> Vec<String> {
+        let parts: Vec<&str> = match &self.separator {
+            Separator::Whitespace => line.split_whitespace().collect(),
+            Separator::None => vec![line.trim()],
+            Separator::Characters(characters) => line
+                .split(|character| characters.contains(&character))
+                .map(str::trim)
+                .collect(),
+            Separator::Regex(regex) => regex.split(line).map(str::trim).collect(),
+        };
+        let mut words = vec![];
+        for part in parts {
+            for word in self.apply_punctuation(part) {
+                if !word.is_empty() {
+                    words.push(word);
+                }
+            }
+        }
+        words
+    }
+
+    /// Whether words can be split off one character at a time as the input is
+    /// read, which a regex or whole lines (`--null-data`) do not allow
+    pub fn splits_by_character(&self) -> bool {
+        matches!(
+            self.separator,
+            Separator::Whitespace | Separator::Characters(_)
+        )
+    }
+
+    /// Whether a character separates words, for [`Tokenizer::splits_by_character`]
+    pub fn is_separator(&self, character: char) -> bool {
+        match &self.separator {
+            Separator::Whitespace => character.is_whitespace(),
+            Separator::Characters(characters) => characters.contains(&character),
+            Separator::None | Separator::Regex(_) => false,
+        }
+    }
+
+    /// Split a word at, or strip, its hyphens and apostrophes
+    fn apply_punctuation(&self, word: &str) -> Vec<String> {
+        let mut words = vec![String::new()];
+        for character in word.chars() {
+            let rule = if HYPHENS.contains(&character) {
+                &self.hyphens
+            } else if APOSTROPHES.contains(&character) {
+                &self.apostrophes
+            } else {
+                &PunctuationRule::Keep
+            };
+            match rule {
+                PunctuationRule::Keep => words.last_mut().unwrap().push(character),
+                PunctuationRule::Split => words.push(String::new()),
+                PunctuationRule::Strip => {}
+            }
+        }
+        words
+    }
+
+    /// Split every argument into words
+    pub fn split_all(&self, arguments: &[String]) -> Vec<String> {
+        arguments
+            .iter()
+            .flat_map(|argument| self.split(argument))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    fn tokenizer(options: &[&str]) -> Tokenizer {
+        let args = CLI::parse_from(std::iter::once("numeracalc").chain(options.iter().copied()));
+        Tokenizer::new(&args).unwrap()
+    }
+
+    fn split(options: &[&str], line: &str) -> Vec<String> {
+        tokenizer(options).split(line)
+    }
+
+    #[test]
+    fn splits_on_whitespace() {
+        assert_eq!(
+            split(&[], "  love\thate \u{a0}peace\n"),
+            ["love", "hate", "peace"]
+        );
+        assert!(split(&[], " \t ").is_empty());
+    }
+
+    #[test]
+    fn splits_on_custom_delimiters() {
+        assert_eq!(
+            split(&["--delimiter", ",;"], "Mary Jane, John;;Paul "),
+            ["Mary Jane", "John", "Paul"]
+        );
+        assert_eq!(split(&["--delimiter", "\\t"], "a b\tc"), ["a b", "c"]);
+        assert_eq!(
+            split(&["--split-regex", r"\s*\|\s*"], "one | two|three"),
+            ["one", "two", "three"]
+        );
+        // With --null-data each line is a single word
+        assert_eq!(split(&["-z"], " New York "), ["New York"]);
+        assert!(Tokenizer::new(&CLI::parse_from(["numeracalc", "--delimiter", ""])).is_err());
+        assert!(Tokenizer::new(&CLI::parse_from(["numeracalc", "--split-regex", "("])).is_err());
+    }
+
+    #[test]
+    fn keeps_hyphens_and_apostrophes_by_default() {
+        assert_eq!(
+            split(&[], "Mary-Jane O'Brien d’Arc"),
+            ["Mary-Jane", "O'Brien", "d’Arc"]
+        );
+    }
+
+    #[test]
+    fn splits_or_strips_hyphens_and_apostrophes() {
+        let line = "Mary-Jane O'Brien";
+        assert_eq!(
+            split(&["--hyphens", "split"], line),
+            ["Mary", "Jane", "O'Brien"]
+        );
+        assert_eq!(
+            split(&["--hyphens", "strip"], line),
+            ["MaryJane", "O'Brien"]
+        );
+        assert_eq!(
+            split(&["--apostrophes", "split"], line),
+            ["Mary-Jane", "O", "Brien"]
+        );
+        assert_eq!(
+            split(&["--hyphens", "strip", "--apostrophes", "strip"], line),
+            ["MaryJane", "OBrien"]
+        );
+        // Typographic apostrophes and non-breaking hyphens are treated the same
+        assert_eq!(
+            split(
+                &["--hyphens", "split", "--apostrophes", "split"],
+                "Jean‑Luc d’Arc"
+            ),
+            ["Jean", "Luc", "d", "Arc"]
+        );
+        // Leading and doubled punctuation leaves no empty words
+        assert_eq!(split(&["--hyphens", "split"], "-a--b-"), ["a", "b"]);
+    }
+
+    #[test]
+    fn separators_for_streaming() {
+        let whitespace = tokenizer(&[]);
+        assert!(whitespace.splits_by_character());
+        assert!(whitespace.is_separator(' ') && !whitespace.is_separator(','));
+        let delimiter = tokenizer(&["--delimiter", ","]);
+        assert!(delimiter.is_separator(',') && !delimiter.is_separator(' '));
+        assert!(!tokenizer(&["--split-regex", ","]).splits_by_character());
+        assert!(!tokenizer(&["-z"]).splits_by_character());
+    }
+
+    #[test]
+    fn unescapes_escape_sequences() {
+        for (text, expected) in [
+            ("\\t", "\t"),
+            ("a\\nb", "a\nb"),
+            ("\\r\\n", "\r\n"),
+            ("\\0", "\0"),
+            ("\\e[1m", "\x1b[1m"),
+            ("\\\\", "\\"),
+            ("\\,", ","),
+            ("end\\", "end\\"),
+            ("plain", "plain"),
+        ] {
+            assert_eq!(unescape(text), expected, "{text:?}");
+        }
+    }
+}
@@ -0,0 +1,437 @@
+use unicode_normalization::UnicodeNormalization;
+
+use crate::args::Scheme;
+
+/// ISO 9:1995 Cyrillic, including Ukrainian, Belarusian and South Slavic letters
+const ISO_9: &[(char, &str)] = &[
+    ('а', "a"),
+    ('б', "b"),
+    ('в', "v"),
+    ('г', "g"),
+    ('ґ', "g̀"),
+    ('д', "d"),
+    ('ѓ', "ǵ"),
+    ('ђ', "đ"),
+    ('е', "e"),
+    ('ё', "ë"),
+    ('є', "ê"),
+    ('ж', "ž"),
+    ('з', "z"),
+    ('ѕ', "ẑ"),
+    ('и', "i"),
+    ('і', "ì"),
+    ('ї', "ï"),
+    ('й', "j"),
+    ('ј', "ǰ"),
+    ('к', "k"),
+    ('ќ', "ḱ"),
+    ('л', "l"),
+    ('љ', "l̂"),
+    ('м', "m"),
+    ('н', "n"),
+    ('њ', "n̂"),
+    ('о', "o"),
+    ('п', "p"),
+    ('р', "r"),
+    ('с', "s"),
+    ('т', "t"),
+    ('ћ', "ć"),
+    ('у', "u"),
+    ('ў', "ŭ"),
+    ('ф', "f"),
+    ('х', "h"),
+    ('ц', "c"),
+    ('ч', "č"),
+    ('џ', "d̂"),
+    ('ш', "š"),
+    ('щ', "ŝ"),
+    ('ъ', "ʺ"),
+    ('ы', "y"),
+    ('ь', "ʹ"),
+    ('э', "è"),
+    ('ю', "û"),
+    ('я', "â"),
+];
+
+/// BGN/PCGN romanization of Russian, with the extra Ukrainian letters
+const BGN_PCGN: &[(char, &str)] = &[
+    ('а', "a"),
+    ('б', "b"),
+    ('в', "v"),
+    ('г', "g"),
+    ('ґ', "g"),
+    ('д', "d"),
+    ('е', "e"),
+    ('ё', "ë"),
+    ('є', "ye"),
+    ('ж', "zh"),
+    ('з', "z"),
+    ('и', "i"),
+    ('і', "i"),
+    ('ї', "yi"),
+    ('й', "y"),
+    ('к', "k"),
+    ('л', "l"),
+    ('м', "m"),
+    ('н', "n"),
+    ('о', "o"),
+    ('п', "p"),
+    ('р', "r"),
+    ('с', "s"),
+    ('т', "t"),
+    ('у', "u"),
+    ('ф', "f"),
+    ('х', "kh"),
+    ('ц', "ts"),
+    ('ч', "ch"),
+    ('ш', "sh"),
+    ('щ', "shch"),
+    ('ъ', "ʺ"),
+    ('ы', "y"),
+    ('ь', "ʹ"),
+    ('э', "e"),
+    ('ю', "yu"),
+    ('я', "ya"),
+];
+
+/// ELOT 743 Greek, for single letters (digraphs are handled separately)
+const ELOT_743: &[(char, &str)] = &[
+    ('α', "a"),
+    ('β', "v"),
+    ('γ', "g"),
+    ('δ', "d"),
+    ('ε', "e"),
+    ('ζ', "z"),
+    ('η', "i"),
+    ('θ', "th"),
+    ('ι', "i"),
+    ('κ', "k"),
+    ('λ', "l"),
+    ('μ', "m"),
+    ('ν', "n"),
+    ('ξ', "x"),
+    ('ο', "o"),
+    ('π', "p"),
+    ('ρ', "r"),
+    ('σ', "s"),
+    ('ς', "s"),
+    ('τ', "t"),
+    ('υ', "y"),
+    ('φ', "f"),
+    ('χ', "ch"),
+    ('ψ', "ps"),
+    ('ω', "o"),
+];
+
+/// IAST independent vowels
+const IAST_VOWELS: &[(char, &str)] = &[
+    ('अ', "a"),
+    ('आ', "ā"),
+    ('इ', "i"),
+    ('ई', "ī"),
+    ('उ', "u"),
+    ('ऊ', "ū"),
+    ('ऋ', "ṛ"),
+    ('ॠ', "ṝ"),
+    ('ऌ', "ḷ"),
+    ('ॡ', "ḹ"),
+    ('ए', "e"),
+    ('ऐ', "ai"),
+    ('ओ', "o"),
+    ('औ', "au"),
+];
+
+/// IAST dependent vowel signs (matras)
+const IAST_MATRAS: &[(char, &str)] = &[
+    ('ा', "ā"),
+    ('ि', "i"),
+    ('ी', "ī"),
+    ('ु', "u"),
+    ('ू', "ū"),
+    ('ृ', "ṛ"),
+    ('ॄ', "ṝ"),
+    ('ॢ', "ḷ"),
+    ('ॣ', "ḹ"),
+    ('े', "e"),
+    ('ै', "ai"),
+    ('ो', "o"),
+    ('ौ', "au"),
+];
+
+/// IAST consonants (without the inherent vowel)
+const IAST_CONSONANTS: &[(char, &str)] = &[
+    ('क', "k"),
+    ('ख', "kh"),
+    ('ग', "g"),
+    ('घ', "gh"),
+    ('ङ', "ṅ"),
+    ('च', "c"),
+    ('छ', "ch"),
+    ('ज', "j"),
+    ('झ', "jh"),
+    ('ञ', "ñ"),
+    ('ट', "ṭ"),
+    ('ठ', "ṭh"),
+    ('ड', "ḍ"),
+    ('ढ', "ḍh"),
+    ('ण', "ṇ"),
+    ('त', "t"),
+    ('थ', "th"),
+    ('द', "d"),
+    ('ध', "dh"),
+    ('न', "n"),
+    ('प', "p"),
+    ('फ', "ph"),
+    ('ब', "b"),
+    ('भ', "bh"),
+    ('म', "m"),
+    ('य', "y"),
+    ('र', "r"),
+    ('ल', "l"),
+    ('ळ', "ḷ"),
+    ('व', "v"),
+    ('श', "ś"),
+    ('ष', "ṣ"),
+    ('स', "s"),
+    ('ह', "h"),
+];
+
+/// IAST signs that follow a syllable
+const IAST_SIGNS: &[(char, &str)] = &[('ं', "ṃ"), ('ः', "ḥ"), ('ँ', "m̐"), ('ऽ', "'")];
+
+const VIRAMA: char = '्';
+const NUKTA: char = '़';
+
+fn lookup(table: &[(char, &'static str)], character: char) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|(source, _)| *source == character)
+        .map(|(_, target)| *target)
+}
+
+/// Append a transliteration, capitalizing it if the source letter was uppercase
+fn push_cased(output: &mut String, latin: &str, uppercase: bool) {
+    if uppercase {
+        let mut characters = latin.chars();
+        if let Some(first) = characters.next() {
+            output.extend(first.to_uppercase());
+            output.push_str(characters.as_str());
+        }
+    } else {
+        output.push_str(latin);
+    }
+}
+
+/// Transliterate text to the Latin alphabet using the given scheme
+///
+/// Characters the scheme does not cover (including Latin letters) are kept as they are.
+pub fn transliterate(scheme: &Scheme, text: &str) -> String {
+    match scheme {
+        Scheme::Iso9 => transliterate_cyrillic(text, ISO_9, false),
+        Scheme::BgnPcgn => transliterate_cyrillic(text, BGN_PCGN, true),
+        Scheme::Elot743 => transliterate_greek(text),
+        Scheme::Iast => transliterate_devanagari(text),
+    }
+}
+
+fn transliterate_cyrillic(text: &str, table: &[(char, &'static str)], bgn_pcgn: bool) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut previous: Option<char> = None;
+    for character in text.chars() {
+        let lower = character.to_lowercase().next().unwrap_or(character);
+        let uppercase = character != lower;
+        match lookup(table, lower) {
+            Some(latin) => {
+                // BGN/PCGN writes е and ё as ye and yë at the start of a word and
+                // after a vowel, й, ъ or ь
+                let iotated = bgn_pcgn
+                    && matches!(lower, 'е' | 'ё')
+                    && previous.is_none_or(|previous| {
+                        "аеёиоуыэюяйъьєії".contains(previous) || !previous.is_alphabetic()
+                    });
+                if iotated {
+                    push_cased(&mut output, &format!("y{latin}"), uppercase);
+                } else {
+                    push_cased(&mut output, latin, uppercase);
+                }
+            }
+            None => output.push(character),
+        }
+        previous = Some(lower);
+    }
+    output
+}
+
+fn transliterate_greek(text: &str) -> String {
+    // Drop the tonos but keep the dialytika, which stops two vowels forming a digraph
+    let decomposed: Vec<char> = text.nfd().filter(|mark| *mark != '\u{301}').collect();
+    // Each letter as (lowercase, original, followed by a dialytika)
+    let letters: Vec<(char, char, bool)> = decomposed
+        .iter()
+        .enumerate()
+        .filter(|(_, character)| **character != '\u{308}')
+        .map(|(index, character)| {
+            let lower = character.to_lowercase().next().unwrap_or(*character);
+            let dialytika = decomposed.get(index + 1) == Some(&'\u{308}');
+            (lower, *character, dialytika)
+        })
+        .collect();
+    let is_greek = |character: char| ('α'..='ω').contains(&character);
+    let voiceless = |character: Option<&(char, char, bool)>| match character {
+        Some((next, ..)) => "θκξπσςτφχψ".contains(*next) || !is_greek(*next),
+        None => true,
+    };
+
+    let mut output = String::with_capacity(text.len());
+    let mut index = 0;
+    while index < letters.len() {
+        let (letter, original, _) = letters[index];
+        let next = letters.get(index + 1);
+        let (latin, length) = match (letter, next) {
+            ('α' | 'ε' | 'η', Some(('υ', _, false))) => {
+                let vowel = lookup(ELOT_743, letter).unwrap_or("");
+                let consonant = if voiceless(letters.get(index + 2)) {
+                    "f"
+                } else {
+                    "v"
+                };
+                (format!("{vowel}{consonant}"), 2)
+            }
+            ('ο', Some(('υ', _, false))) => ("ou".to_string(), 2),
+            ('γ', Some(('γ', ..))) => ("ng".to_string(), 2),
+            ('γ', Some(('ξ', ..))) => ("nx".to_string(), 2),
+            ('γ', Some(('χ', ..))) => ("nch".to_string(), 2),
+            ('μ', Some(('π', ..))) => {
+                let word_start = index == 0 || !is_greek(letters[index - 1].0);
+                let word_end = letters
+                    .get(index + 2)
+                    .is_none_or(|(after, ..)| !is_greek(*after));
+                if word_start || word_end {
+                    ("b".to_string(), 2)
+                } else {
+                    ("mp".to_string(), 2)
+                }
+            }
+            _ => match lookup(ELOT_743, letter) {
+                Some(latin) => (latin.to_string(), 1),
+                None => {
+                    output.push(original);
+                    index += 1;
+                    continue;
+                }
+            },
+        };
+        push_cased(&mut output, &latin, original != letter);
+        index += length;
+    }
+    output
+}
+
+fn transliterate_devanagari(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut characters = text
+        .chars()
+        .filter(|character| *character != NUKTA)
+        .peekable();
+    while let Some(character) = characters.next() {
+        if let Some(consonant) = lookup(IAST_CONSONANTS, character) {
+            output.push_str(consonant);
+            // A consonant carries an inherent "a" unless a vowel sign or virama follows
+            match characters.peek() {
+                Some(&VIRAMA) => {
+                    characters.next();
+                }
+                Some(next) if lookup(IAST_MATRAS, *next).is_some() => {}
+                _ => output.push('a'),
+            }
+        } else if let Some(vowel) = lookup(IAST_VOWELS, character)
+            .or_else(|| lookup(IAST_MATRAS, character))
+            .or_else(|| lookup(IAST_SIGNS, character))
+        {
+            output.push_str(vowel);
+        } else if let Some(digit) = ('०'..='९').position(|digit| digit == character) {
+            output.push_str(&digit.to_string());
+        } else {
+            output.push(character);
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(scheme: Scheme, cases: &[(&str, &str)]) {
+        for (text, expected) in cases {
+            assert_eq!(transliterate(&scheme, text), *expected, "{text}");
+        }
+    }
+
+    #[test]
+    fn iso_9() {
+        check(
+            Scheme::Iso9,
+            &[
+                ("Москва", "Moskva"),
+                ("Жук", "Žuk"),
+                ("Щука", "Ŝuka"),
+                ("Ельцин", "Elʹcin"),
+                ("Latin", "Latin"),
+            ],
+        );
+    }
+
+    #[test]
+    fn bgn_pcgn() {
+        check(
+            Scheme::BgnPcgn,
+            &[
+                ("Москва", "Moskva"),
+                ("Щука", "Shchuka"),
+                // е and ё are iotated at the start of a word, after a vowel, ъ or ь
+                ("Ельцин", "Yelʹtsin"),
+                ("Пётр", "Pëtr"),
+                ("Соловьёв", "Solovʹyëv"),
+                ("объект", "obʺyekt"),
+                ("Киев", "Kiyev"),
+            ],
+        );
+    }
+
+    #[test]
+    fn elot_743() {
+        check(
+            Scheme::Elot743,
+            &[
+                ("Ελλάδα", "Ellada"),
+                ("Αθήνα", "Athina"),
+                ("Ουρανός", "Ouranos"),
+                // αυ and ευ are af and ef before voiceless consonants, av and ev otherwise
+                ("αυτό", "afto"),
+                ("Ευρώπη", "Evropi"),
+                ("ευχαριστώ", "efcharisto"),
+                // A dialytika keeps the vowels apart
+                ("Μαΐου", "Maiou"),
+                // μπ is b at the start or end of a word and mp inside it
+                ("μπαμπάς", "bampas"),
+                ("Ευαγγέλιο", "Evangelio"),
+            ],
+        );
+    }
+
+    #[test]
+    fn iast() {
+        check(
+            Scheme::Iast,
+            &[
+                ("नमस्ते", "namaste"),
+                ("योग", "yoga"),
+                ("भारत", "bhārata"),
+                // The virama removes the inherent vowel
+                ("संस्कृतम्", "saṃskṛtam"),
+                ("१९९०", "1990"),
+            ],
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use rust_xlsxwriter::{ConditionalFormat3ColorScale, Format, Workbook, Worksheet, XlsxError};
+
+use crate::data::VALUE_TABLE;
+use crate::evaluate::{reduce, WordValue};
+
+/// Fill colors of the value scale, from low (blue) through middle (yellow) to high (red)
+const LOW_COLOR: u32 = 0x9BC2E6;
+const MIDDLE_COLOR: u32 = 0xFFE699;
+const HIGH_COLOR: u32 = 0xF4B183;
+
+/// Reduced values listed in the distribution of the summary sheet
+const REDUCED_VALUES: [u32; 13] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 11, 22, 33];
+
+/// A workbook of word values for `--output`
+pub struct XlsxReport {
+    path: String,
+    words: Worksheet,
+    /// Next row of the words sheet
+    row: u32,
+    file_totals: Vec<(String, u64)>,
+    /// Number of words with each reduced value
+    distribution: [u32; 34],
+}
+
+fn header_format() -> Format {
+    Format::new().set_bold()
+}
+
+/// Add a fill color scale to a range of a column
+fn color_scale(worksheet: &mut Worksheet, column: u16, last_row: u32) -> Result<(), XlsxError> {
+    if last_row == 0 {
+        return Ok(());
+    }
+    let scale = ConditionalFormat3ColorScale::new()
+        .set_minimum_color(LOW_COLOR)
+        .set_midpoint_color(MIDDLE_COLOR)
+        .set_maximum_color(HIGH_COLOR);
+    worksheet.add_conditional_format(1, column, last_row, column, &scale)?;
+    Ok(())
+}
+
+/// Write a header row and freeze it
+fn write_header(worksheet: &mut Worksheet, titles: &[&str]) -> Result<(), XlsxError> {
+    for (column, title) in titles.iter().enumerate() {
+        worksheet.write_string_with_format(0, column as u16, *title, &header_format())?;
+    }
+    worksheet.set_freeze_panes(1, 0)?;
+    Ok(())
+}
+
+impl XlsxReport {
+    pub fn new(path: &str) -> Result<XlsxReport, String> {
+        let mut words = Worksheet::new();
+        words
+            .set_name("Words")
+            .and_then(|words| write_header(words, &["Word", "Value", "Reduced", "Letters", "File"]))
+            .map_err(|e| format!("Failed to create {path:?}: {e}"))?;
+        Ok(XlsxReport {
+            path: path.to_string(),
+            words,
+            row: 1,
+            file_totals: vec![],
+            distribution: [0; 34],
+        })
+    }
+
+    /// Add a row for a word
+    pub fn add_word(
+        &mut self,
+        word: &str,
+        file: Option<&str>,
+        word_value: &WordValue,
+    ) -> Result<(), String> {
+        let reduced = reduce(word_value.value, true);
+        let letters: Vec<String> = word_value
+            .letters
+            .iter()
+            .map(|letter| format!("{}={}", letter.text, letter.value))
+            .collect();
+        let row = self.row;
+        let result = self
+            .words
+            .write_string(row, 0, word)
+            .and_then(|words| words.write_number(row, 1, word_value.value as f64))
+            .and_then(|words| words.write_number(row, 2, reduced))
+            .and_then(|words| words.write_string(row, 3, letters.join(" ")))
+            .and_then(|words| words.write_string(row, 4, file.unwrap_or_default()));
+        result.map_err(|e| format!("Failed to write {:?}: {e}", self.path))?;
+        self.row += 1;
+        self.distribution[reduced as usize] += 1;
+        Ok(())
+    }
+
+    /// Record the total of a file for the summary sheet
+    pub fn add_file_total(&mut self, file: &str, total: u64) {
+        self.file_totals.push((file.to_string(), total));
+    }
+
+    /// Add the cipher and summary sheets and save the workbook
+    pub fn save(self, total: u64) -> Result<(), String> {
+        let path = self.path.clone();
+        self.write_sheets(total)
+            .map_err(|e| format!("Failed to write {path:?}: {e}"))
+    }
+
+    fn write_sheets(mut self, total: u64) -> Result<(), XlsxError> {
+        let last_row = self.row - 1;
+        color_scale(&mut self.words, 1, last_row)?;
+        color_scale(&mut self.words, 2, last_row)?;
+        self.words.autofit();
+
+        let mut cipher = Worksheet::new();
+        cipher.set_name("Cipher")?;
+        write_header(&mut cipher, &["Character", "Value"])?;
+        let mut row = 1;
+        for (character, value) in &VALUE_TABLE {
+            cipher.write_string(row, 0, character.to_string())?;
+            cipher.write_number(row, 1, *value)?;
+            row += 1;
+        }
+        color_scale(&mut cipher, 1, row - 1)?;
+        cipher.autofit();
+
+        let mut summary = Worksheet::new();
+        summary.set_name("Summary")?;
+        write_header(&mut summary, &["Total", "Value"])?;
+        summary.write_string(1, 0, "Words")?;
+        summary.write_number(1, 1, last_row)?;
+        summary.write_string(2, 0, "Total Value")?;
+        summary.write_number(2, 1, total as f64)?;
+        summary.write_string(3, 0, "Reduced Total")?;
+        summary.write_number(3, 1, reduce(total, true))?;
+        let mut row = 4;
+        for (file, file_total) in &self.file_totals {
+            summary.write_string(row, 0, format!("File Total: {file}"))?;
+            summary.write_number(row, 1, *file_total as f64)?;
+            row += 1;
+        }
+
+        // The distribution of reduced values, below the totals
+        row += 1;
+        let bold = header_format();
+        summary.write_string_with_format(row, 0, "Reduced Value", &bold)?;
+        summary.write_string_with_format(row, 1, "Words", &bold)?;
+        let first_row = row + 1;
+        for value in REDUCED_VALUES {
+            row += 1;
+            summary.write_number(row, 0, value)?;
+            summary.write_number(row, 1, self.distribution[value as usize])?;
+        }
+        let scale = ConditionalFormat3ColorScale::new()
+            .set_minimum_color(LOW_COLOR)
+            .set_midpoint_color(MIDDLE_COLOR)
+            .set_maximum_color(HIGH_COLOR);
+        summary.add_conditional_format(first_row, 1, row, 1, &scale)?;
+        summary.autofit();
+
+        let mut workbook = Workbook::new();
+        workbook.push_worksheet(self.words);
+        workbook.push_worksheet(cipher);
+        workbook.push_worksheet(summary);
+        workbook.save(&self.path)
+    }
+}